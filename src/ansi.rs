@@ -0,0 +1,238 @@
+use colored::Color;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// A piece of text as it is written to the terminal: either an escape sequence, which takes up no columns, or text
+/// which is actually displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Segment<'a> {
+    Escape(&'a str),
+    Text(&'a str),
+}
+
+/// Splits text into escape sequences and displayed text.
+pub(crate) fn segments(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut text_start = 0;
+    let mut index = 0;
+
+    while index < text.len() {
+        let Some(length) = escape_len(&text.as_bytes()[index..]) else {
+            index += 1;
+            continue;
+        };
+
+        if text_start < index {
+            segments.push(Segment::Text(&text[text_start..index]));
+        }
+        segments.push(Segment::Escape(&text[index..index + length]));
+        index += length;
+        text_start = index;
+    }
+
+    if text_start < text.len() {
+        segments.push(Segment::Text(&text[text_start..]));
+    }
+
+    segments
+}
+
+/// Length of the escape sequence at the start of `bytes`, if there is one.
+///
+/// Recognizes CSI sequences (which includes the SGR sequences used for colors) and OSC sequences (like hyperlinks)
+/// terminated by either BEL or ST. Unterminated sequences are not escape sequences and are left to be displayed.
+fn escape_len(bytes: &[u8]) -> Option<usize> {
+    if bytes.first() != Some(&0x1b) {
+        return None;
+    }
+
+    match bytes.get(1)? {
+        b'[' => {
+            // Any number of parameter and intermediate bytes, closed by a single final byte.
+            let end = bytes[2..]
+                .iter()
+                .position(|byte| !(0x20..=0x3f).contains(byte))?;
+            (0x40..=0x7e).contains(&bytes[2 + end]).then_some(end + 3)
+        }
+        b']' => {
+            let mut index = 2;
+            while index < bytes.len() {
+                match bytes[index] {
+                    0x07 => return Some(index + 1),
+                    0x1b if bytes.get(index + 1) == Some(&b'\\') => return Some(index + 2),
+                    _ => index += 1,
+                }
+            }
+            None
+        }
+        0x40..=0x5f => Some(2),
+        _ => None,
+    }
+}
+
+/// Keeps track of the styles and hyperlink that are active at some point in a line of text, so that they can be
+/// closed at the end of the line and opened again on the next one.
+#[derive(Debug, Default)]
+pub(crate) struct ActiveStyles {
+    sgr: Vec<String>,
+    hyperlink: Option<String>,
+}
+
+impl ActiveStyles {
+    /// Updates the active styles with an escape sequence that was just written.
+    pub(crate) fn observe(&mut self, escape: &str) {
+        if let Some(params) = escape
+            .strip_prefix("\x1b[")
+            .and_then(|rest| rest.strip_suffix('m'))
+        {
+            let first = params.split(';').next().unwrap_or_default();
+            if first.is_empty() || first == "0" {
+                self.sgr.clear();
+            }
+            if !params
+                .split(';')
+                .all(|param| param.is_empty() || param == "0")
+            {
+                self.sgr.push(escape.to_string());
+            }
+            return;
+        }
+
+        if let Some(rest) = escape.strip_prefix("\x1b]8;") {
+            let rest = rest
+                .strip_suffix('\x07')
+                .or_else(|| rest.strip_suffix("\x1b\\"))
+                .unwrap_or(rest);
+            let url = rest.split_once(';').map(|(_, url)| url).unwrap_or_default();
+            self.hyperlink = (!url.is_empty()).then(|| escape.to_string());
+        }
+    }
+
+    /// Escape sequences which turn off every active style.
+    pub(crate) fn close(&self) -> String {
+        let mut close = String::new();
+        if self.hyperlink.is_some() {
+            close += "\x1b]8;;\x1b\\";
+        }
+        if !self.sgr.is_empty() {
+            close += "\x1b[0m";
+        }
+        close
+    }
+
+    /// Escape sequences which turn every active style back on.
+    pub(crate) fn reopen(&self) -> String {
+        let mut reopen = self.sgr.concat();
+        if let Some(hyperlink) = &self.hyperlink {
+            reopen += hyperlink;
+        }
+        reopen
+    }
+}
+
+/// Wraps text in the SGR sequences for a color and style. Formatters always write these, and they are stripped again
+/// by [`StripColors`] when colors are turned off.
+pub(crate) fn paint(text: &str, color: Option<Color>, bold: bool, dimmed: bool) -> String {
+    let mut params = Vec::new();
+    if bold {
+        params.push("1".into());
+    }
+    if dimmed {
+        params.push("2".into());
+    }
+    if let Some(color) = color {
+        params.push(color.to_fg_str());
+    }
+
+    if params.is_empty() {
+        return text.to_string();
+    }
+
+    format!("\x1b[{}m{text}\x1b[0m", params.join(";"))
+}
+
+pub(crate) fn bold(text: &str) -> String {
+    paint(text, None, true, false)
+}
+
+pub(crate) fn dimmed(text: &str) -> String {
+    paint(text, None, false, true)
+}
+
+/// Whether an escape sequence sets colors or styles.
+fn is_sgr(escape: &[u8]) -> bool {
+    escape.starts_with(b"\x1b[") && escape.ends_with(b"m")
+}
+
+/// Removes colors and styles from text, keeping any other escape sequences like hyperlinks.
+pub(crate) fn strip_colors(text: &str) -> String {
+    segments(text)
+        .into_iter()
+        .filter_map(|segment| match segment {
+            Segment::Escape(escape) if is_sgr(escape.as_bytes()) => None,
+            Segment::Escape(text) | Segment::Text(text) => Some(text),
+        })
+        .collect()
+}
+
+/// Whether `bytes` starts a CSI sequence which hasn't been completed yet.
+fn is_partial_csi(bytes: &[u8]) -> bool {
+    match bytes {
+        [0x1b] => true,
+        [0x1b, b'[', rest @ ..] => rest.iter().all(|byte| (0x20..=0x3f).contains(byte)),
+        _ => false,
+    }
+}
+
+/// Writer which removes colors and styles from everything written to a target, used by formatters which have colors
+/// turned off. A sequence split across two writes is held back until the rest of it arrives.
+pub(crate) struct StripColors {
+    inner: Arc<Mutex<dyn Write + Send>>,
+    pending: Vec<u8>,
+}
+
+impl StripColors {
+    pub fn wrap(inner: Arc<Mutex<dyn Write + Send>>) -> Arc<Mutex<dyn Write + Send>> {
+        Arc::new(Mutex::new(StripColors {
+            inner,
+            pending: Vec::new(),
+        }))
+    }
+}
+
+impl Write for StripColors {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+
+        let mut stripped = Vec::with_capacity(self.pending.len());
+        let mut index = 0;
+        while index < self.pending.len() {
+            let rest = &self.pending[index..];
+            if let Some(length) = escape_len(rest) {
+                if !is_sgr(&rest[..length]) {
+                    stripped.extend_from_slice(&rest[..length]);
+                }
+                index += length;
+            } else if is_partial_csi(rest) {
+                break;
+            } else {
+                stripped.push(rest[0]);
+                index += 1;
+            }
+        }
+        self.pending.drain(..index);
+
+        self.inner
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .write_all(&stripped)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let pending = std::mem::take(&mut self.pending);
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        inner.write_all(&pending)?;
+        inner.flush()
+    }
+}
@@ -41,6 +41,14 @@ fn main() {
     json.finish();
     sleep();
 
+    println!("--- YAML formatter ---");
+    let mut yaml = new(Format::Yaml, Options::default());
+    yaml.println(&"plain string");
+    yaml.success(&"ok");
+    yaml.warning(&"heads up");
+    yaml.finish();
+    sleep();
+
     println!("--- Spinner formatter ---");
     let mut spinner = new(
         Format::Spinner,
@@ -54,6 +62,32 @@ fn main() {
     spinner.print(&"Still going");
     spinner.println(&"Working hard");
     thread::sleep(Duration::from_millis(1400));
+    let progress = spinner.progress(20, &"Downloading");
+    for _ in 0..20 {
+        progress.inc(1);
+        thread::sleep(Duration::from_millis(50));
+    }
+    progress.finish();
+    let tasks: Vec<_> = ["build api", "build web", "run migrations"]
+        .into_iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let task = spinner.task(&name);
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(400 * (index as u64 + 1)));
+                task.set_message(&"almost there");
+                thread::sleep(Duration::from_millis(400));
+                if index == 1 {
+                    task.error(&"failed");
+                } else {
+                    task.success(&"done");
+                }
+            })
+        })
+        .collect();
+    for task in tasks {
+        let _ = task.join();
+    }
     spinner.success(&"Done");
     spinner.finish();
 
@@ -0,0 +1,112 @@
+//! Standard output flags for [clap](https://docs.rs/clap) based command line tools.
+//!
+//! [`OutputArgs`] bundles the `--format`, `--no-color`, `--debug`, `--quiet` and `--verbose` flags so they can be
+//! flattened into any clap parser and turned into a configured formatter:
+//!
+//! ```rust
+//! use clap::Parser;
+//! use polyfmt::cli::OutputArgs;
+//!
+//! #[derive(Parser)]
+//! struct Cli {
+//!     #[command(flatten)]
+//!     output: OutputArgs,
+//! }
+//!
+//! let cli = Cli::parse_from(["mytool", "--format", "json", "-v"]);
+//! let mut fmt = cli.output.formatter();
+//! fmt.debug(&"shown because of -v");
+//! ```
+//!
+//! Tools which also read their settings from the environment can use [`OutputArgs::formatter_from_env`] instead, so
+//! that flags win over `MYAPP_*` variables and `MYAPP_FORMAT` is used when `--format` isn't passed.
+//!
+//! This module is only available with the `clap` feature.
+
+use crate::env::{format_from_env, options_from_env};
+use crate::{ColorMode, EnvError, Format, Formatter, Options, Verbosity};
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use std::str::FromStr;
+use strum::VariantNames;
+
+/// Output related command line flags shared by every tool using polyfmt.
+#[derive(Debug, Clone, PartialEq, Eq, clap::Args)]
+pub struct OutputArgs {
+    /// Output format [default: auto]
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        ignore_case = true,
+        value_parser = PossibleValuesParser::new(Format::VARIANTS)
+            .try_map(|format| Format::from_str(&format)),
+    )]
+    pub format: Option<Format>,
+
+    /// Disable colored output
+    #[arg(long)]
+    pub no_color: bool,
+
+    /// Print debug output, same as -v
+    #[arg(long)]
+    pub debug: bool,
+
+    /// Print less output; pass twice to only print errors
+    #[arg(short, long, action = clap::ArgAction::Count, conflicts_with_all = ["verbose", "debug"])]
+    pub quiet: u8,
+
+    /// Print more output; pass twice to include trace output
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+}
+
+impl OutputArgs {
+    /// Applies the flags on top of `options`. Settings the user didn't pass a flag for are left alone, so the flags
+    /// can be layered over [`Options::from_env`] or options the tool picked itself.
+    pub fn apply(&self, options: Options) -> Options {
+        let mut options = options;
+
+        if self.no_color {
+            options = options.with_color(ColorMode::Never);
+        }
+
+        if self.quiet > 0 || self.verbose > 0 || self.debug {
+            let verbose = self.verbose.max(u8::from(self.debug));
+            options = options.with_verbosity(Verbosity::from_flags(self.quiet, verbose));
+        }
+
+        options
+    }
+
+    /// Creates a formatter in the chosen format, configured by the flags on top of [`Options::default`].
+    pub fn formatter(&self) -> Box<dyn Formatter> {
+        self.formatter_with(Options::default())
+    }
+
+    /// Creates a formatter in the chosen format, configured by the flags on top of `options`. Without `--format`
+    /// the format is [`Format::Auto`].
+    pub fn formatter_with(&self, options: Options) -> Box<dyn Formatter> {
+        let format = self.format.clone().unwrap_or(Format::Auto);
+        crate::new(format, self.apply(options))
+    }
+
+    /// Creates a formatter configured by the flags on top of [`Options::from_env`]. Without `--format` the format
+    /// is read with [`Format::from_env`].
+    pub fn formatter_from_env(&self, prefix: &str) -> Result<Box<dyn Formatter>, EnvError> {
+        self.formatter_from(prefix, |key| std::env::var(key).ok())
+    }
+
+    /// Reads the environment through `env` so tests don't have to touch the process environment.
+    pub(crate) fn formatter_from(
+        &self,
+        prefix: &str,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Box<dyn Formatter>, EnvError> {
+        let format = match &self.format {
+            Some(format) => format.clone(),
+            None => format_from_env(prefix, &env)?,
+        };
+        let options = options_from_env(prefix, &env)?;
+
+        Ok(crate::new(format, self.apply(options)))
+    }
+}
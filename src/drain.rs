@@ -0,0 +1,74 @@
+use std::io::{self, ErrorKind, Write};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Exit status used when the process exits because of a broken pipe. This is what shells report for a process
+/// killed by `SIGPIPE` (128 + 13).
+pub const BROKEN_PIPE_EXIT_STATUS: i32 = 141;
+
+/// Writer which wraps an output target and stops writing to it once the reading end of a pipe has gone away.
+///
+/// CLIs are commonly piped into programs like `head` that exit early, after which every write fails with
+/// [`ErrorKind::BrokenPipe`]. Rather than surfacing that error on every call the target is drained: the error is
+/// swallowed and anything written afterwards is discarded. If `exit_on_broken_pipe` is set the process exits quietly
+/// with [`BROKEN_PIPE_EXIT_STATUS`] instead.
+pub(crate) struct Drain {
+    inner: Arc<Mutex<dyn Write + Send>>,
+    exit_on_broken_pipe: bool,
+    drained: bool,
+}
+
+impl Drain {
+    pub fn wrap(
+        inner: Arc<Mutex<dyn Write + Send>>,
+        exit_on_broken_pipe: bool,
+    ) -> Arc<Mutex<dyn Write + Send>> {
+        Arc::new(Mutex::new(Drain {
+            inner,
+            exit_on_broken_pipe,
+            drained: false,
+        }))
+    }
+
+    /// Turns a broken pipe error into success, draining the target from then on.
+    fn check<T>(&mut self, result: io::Result<T>, drained_value: T) -> io::Result<T> {
+        match result {
+            Err(err) if err.kind() == ErrorKind::BrokenPipe => {
+                if self.exit_on_broken_pipe {
+                    std::process::exit(BROKEN_PIPE_EXIT_STATUS);
+                }
+
+                self.drained = true;
+                Ok(drained_value)
+            }
+            other => other,
+        }
+    }
+}
+
+impl Write for Drain {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.drained {
+            return Ok(buf.len());
+        }
+
+        let result = self
+            .inner
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .write(buf);
+        self.check(result, buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.drained {
+            return Ok(());
+        }
+
+        let result = self
+            .inner
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .flush();
+        self.check(result, ())
+    }
+}
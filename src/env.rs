@@ -0,0 +1,227 @@
+use crate::{ColorMode, Format, Options, Timestamps, Verbosity};
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    io,
+    path::PathBuf,
+    str::FromStr,
+};
+
+/// Error returned by [`Options::from_env`] and [`Format::from_env`] when an environment variable holds a value
+/// polyfmt can't use.
+#[derive(Debug)]
+pub enum EnvError {
+    /// The variable is set to something that can't be parsed.
+    InvalidValue {
+        variable: String,
+        value: String,
+        expected: &'static str,
+    },
+
+    /// The output file named by the variable couldn't be created.
+    OutputFile {
+        variable: String,
+        path: PathBuf,
+        source: io::Error,
+    },
+}
+
+impl Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvError::InvalidValue {
+                variable,
+                value,
+                expected,
+            } => write!(
+                f,
+                "invalid value {value:?} for {variable}: expected {expected}"
+            ),
+            EnvError::OutputFile {
+                variable,
+                path,
+                source,
+            } => write!(
+                f,
+                "could not create output file {} from {variable}: {source}",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl Error for EnvError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EnvError::InvalidValue { .. } => None,
+            EnvError::OutputFile { source, .. } => Some(source),
+        }
+    }
+}
+
+impl Format {
+    /// Reads the format from the `<PREFIX>_FORMAT` environment variable, falling back to [`Format::Auto`] when it
+    /// isn't set. Accepts any format name regardless of case.
+    ///
+    /// ```rust
+    /// # use polyfmt::{new, Format, Options};
+    /// let format = Format::from_env("MYAPP")?;
+    /// let mut fmt = new(format, Options::from_env("MYAPP")?);
+    /// # Ok::<(), polyfmt::EnvError>(())
+    /// ```
+    pub fn from_env(prefix: &str) -> Result<Format, EnvError> {
+        format_from_env(prefix, |key| std::env::var(key).ok())
+    }
+}
+
+impl Options {
+    /// Builds options from environment variables named after `prefix`, starting from [`Options::default`].
+    ///
+    /// | Variable | Values |
+    /// |----------|--------|
+    /// | `<PREFIX>_VERBOSITY` | `error`, `warn`, `info`, `debug` or `trace` |
+    /// | `<PREFIX>_DEBUG` | `1`, `true`, `yes`, `on` or `0`, `false`, `no`, `off` |
+    /// | `<PREFIX>_COLOR` | `auto`, `always` or `never` |
+    /// | `<PREFIX>_MAX_LINE_LENGTH` | a positive number of columns |
+    /// | `<PREFIX>_PADDING` | a number of columns |
+    /// | `<PREFIX>_TIMESTAMPS` | `off`, `rfc3339` or `elapsed` |
+    /// | `<PREFIX>_OUTPUT` | path of a file to write output to instead of stdout; it is created or truncated |
+    ///
+    /// Variables set to an empty string count as unset. When several variables control the same setting the more
+    /// specific one wins: `<PREFIX>_VERBOSITY` over `<PREFIX>_DEBUG`, and `<PREFIX>_COLOR` over the `NO_COLOR` and
+    /// `CLICOLOR_FORCE` conventions. Builder methods called on the result override the environment entirely.
+    ///
+    /// Invalid values are reported as an [`EnvError`] naming the variable instead of being ignored.
+    pub fn from_env(prefix: &str) -> Result<Options, EnvError> {
+        options_from_env(prefix, |key| std::env::var(key).ok())
+    }
+}
+
+/// Reads the prefixed variables through `env` so tests don't have to touch the process environment.
+pub(crate) fn format_from_env(
+    prefix: &str,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<Format, EnvError> {
+    let reader = Reader { prefix, env };
+    Ok(reader
+        .parse(
+            "FORMAT",
+            "one of plain, tree, spinner, json, yaml, silent or auto",
+        )?
+        .unwrap_or(Format::Auto))
+}
+
+pub(crate) fn options_from_env(
+    prefix: &str,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<Options, EnvError> {
+    let reader = Reader { prefix, env };
+    let mut options = Options::default();
+
+    if let Some(verbosity) =
+        reader.parse::<Verbosity>("VERBOSITY", "one of error, warn, info, debug or trace")?
+    {
+        options.verbosity = verbosity;
+    } else if let Some(debug) = reader.flag("DEBUG")? {
+        options = options.with_debug(debug);
+    }
+
+    if let Some(color) = reader.parse::<ColorMode>("COLOR", "one of auto, always or never")? {
+        options.color = color;
+    } else if (reader.env)("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        options.color = ColorMode::Never;
+    } else if (reader.env)("CLICOLOR_FORCE").is_some_and(|value| !value.is_empty() && value != "0")
+    {
+        options.color = ColorMode::Always;
+    }
+
+    if let Some(max_line_length) = reader.parse::<usize>("MAX_LINE_LENGTH", "a positive number")? {
+        if max_line_length == 0 {
+            return Err(reader.invalid("MAX_LINE_LENGTH", "0", "a positive number"));
+        }
+        options.max_line_length = max_line_length;
+    }
+
+    if let Some(padding) = reader.parse::<u16>("PADDING", "a number between 0 and 65535")? {
+        options.padding = padding;
+    }
+
+    if let Some(timestamps) =
+        reader.parse::<Timestamps>("TIMESTAMPS", "one of off, rfc3339 or elapsed")?
+    {
+        options.timestamps = timestamps;
+    }
+
+    if let Some(path) = reader.get("OUTPUT") {
+        let file = std::fs::File::create(&path).map_err(|source| EnvError::OutputFile {
+            variable: reader.name("OUTPUT"),
+            path: PathBuf::from(&path),
+            source,
+        })?;
+        options = options.with_custom_output_target(file);
+    }
+
+    Ok(options)
+}
+
+struct Reader<'a, F> {
+    prefix: &'a str,
+    env: F,
+}
+
+impl<F: Fn(&str) -> Option<String>> Reader<'_, F> {
+    fn name(&self, suffix: &str) -> String {
+        let prefix = self.prefix.trim_end_matches('_');
+        if prefix.is_empty() {
+            return suffix.to_string();
+        }
+
+        format!("{prefix}_{suffix}")
+    }
+
+    /// Returns the trimmed value of the prefixed variable, treating empty values as unset.
+    fn get(&self, suffix: &str) -> Option<String> {
+        (self.env)(&self.name(suffix))
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+
+    fn parse<T: FromStr>(
+        &self,
+        suffix: &str,
+        expected: &'static str,
+    ) -> Result<Option<T>, EnvError> {
+        let Some(value) = self.get(suffix) else {
+            return Ok(None);
+        };
+
+        value
+            .parse()
+            .map(Some)
+            .map_err(|_| self.invalid(suffix, &value, expected))
+    }
+
+    fn flag(&self, suffix: &str) -> Result<Option<bool>, EnvError> {
+        let Some(value) = self.get(suffix) else {
+            return Ok(None);
+        };
+
+        match value.to_ascii_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => Ok(Some(true)),
+            "0" | "false" | "no" | "off" => Ok(Some(false)),
+            _ => Err(self.invalid(
+                suffix,
+                &value,
+                "one of 1, true, yes, on, 0, false, no or off",
+            )),
+        }
+    }
+
+    fn invalid(&self, suffix: &str, value: &str, expected: &'static str) -> EnvError {
+        EnvError::InvalidValue {
+            variable: self.name(suffix),
+            value: value.to_string(),
+            expected,
+        }
+    }
+}
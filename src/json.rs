@@ -1,33 +1,103 @@
-use crate::{take_and_check_allowed, Displayable, Format, Formatter, IndentGuard, Options};
+use crate::drain::Drain;
+use crate::progress::{NoopProgress, ProgressState};
+use crate::schema::{Event, Message, Record, SCHEMA_VERSION, TaskStatus};
+use crate::task::NoopTask;
+use crate::timestamp::Clock;
+use crate::{
+    take_and_check_allowed, Destination, Displayable, Field, Format, Formatter, IndentGuard,
+    JsonMode, Options, ProgressHandle, Routing, TaskHandle, Verbosity, WriteErrors,
+};
 use serde_json::json;
-use std::sync::{Arc, Mutex, Weak};
-use std::{collections::HashSet, io::Write};
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+use std::time::{Duration, Instant};
+use std::{
+    collections::HashSet,
+    io::{self, BufRead, Write},
+};
+
+/// Minimum amount of time between two progress records. Updates in between are skipped, but the last position is
+/// still written when the handle is finished or dropped.
+const PROGRESS_THROTTLE: Duration = Duration::from_millis(250);
+
+/// How records are encoded. Both encodings write the very same records, so [`Yaml`](Format::Yaml) is the Json
+/// formatter with a different encoder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Json,
+    Yaml,
+}
+
+impl Encoding {
+    fn format(self) -> Format {
+        match self {
+            Encoding::Json => Format::Json,
+            Encoding::Yaml => Format::Yaml,
+        }
+    }
+}
 
-#[derive(Clone)]
 pub struct Json {
-    pub debug: bool,
+    pub verbosity: Verbosity,
+    encoding: Encoding,
     allowed_formats: HashSet<Format>,
+    fields: Vec<Field>,
+    /// Fields set with `with_metadata`, kept apart so that `with_fields` doesn't replace them.
+    metadata: Vec<Field>,
     output_target: Arc<Mutex<dyn Write + Send>>,
+    diagnostic_target: Arc<Mutex<dyn Write + Send>>,
+    routing: Routing,
+    write_errors: WriteErrors,
+    mode: JsonMode,
+    pretty: bool,
+    /// Records held back for the output target until the document is written. Only used outside of
+    /// [`JsonMode::Lines`].
+    records: Vec<serde_json::Value>,
+    /// Whether the document has been written yet. Nothing can be added to it afterwards.
+    document_written: bool,
+    /// Ids of the currently open indentation groups, outermost first.
+    groups: Vec<u64>,
+    /// Id handed to the next indentation group.
+    next_group: u64,
+    clock: Clock,
 }
 
 impl Json {
-    pub fn new(options: Options) -> Arc<Mutex<Self>> {
+    pub fn new(options: Options, encoding: Encoding) -> Arc<Mutex<Self>> {
         Arc::new(Mutex::new(Json {
-            debug: options.debug,
+            verbosity: options.verbosity,
+            encoding,
             allowed_formats: HashSet::new(),
-            output_target: options.output_target.target,
+            fields: Vec::new(),
+            metadata: Vec::new(),
+            output_target: Drain::wrap(options.output_target.target, options.exit_on_broken_pipe),
+            diagnostic_target: Drain::wrap(
+                options.diagnostic_target.target,
+                options.exit_on_broken_pipe,
+            ),
+            routing: options.routing,
+            write_errors: WriteErrors::new(options.write_error_policy),
+            mode: options.json_mode,
+            pretty: options.json_pretty,
+            records: Vec::new(),
+            document_written: false,
+            groups: Vec::new(),
+            next_group: 1,
+            clock: Clock::new(options.timestamps),
         }))
     }
 }
 
 struct Guard {
     fmtter: Weak<Mutex<Json>>,
+    /// Id of the group opened by the guard, so that guards dropped out of order close their own group.
+    group: u64,
 }
 
 impl Guard {
-    fn new(fmtter: Arc<Mutex<Json>>) -> Self {
+    fn new(fmtter: Arc<Mutex<Json>>, group: u64) -> Self {
         Self {
             fmtter: Arc::downgrade(&fmtter),
+            group,
         }
     }
 }
@@ -38,129 +108,454 @@ impl Drop for Guard {
     fn drop(&mut self) {
         if let Some(fmtter) = self.fmtter.upgrade() {
             let mut fmtter_lock = fmtter.lock().unwrap();
-            fmtter_lock.outdent();
+            fmtter_lock.close_group(self.group);
         }
     }
 }
 
-impl Json {
-    fn print(&mut self, msg: &dyn Displayable) {
-        if !take_and_check_allowed(Format::Json, &mut self.allowed_formats) {
+/// Progress handle which reports progress as `progress` records.
+struct JsonProgress {
+    fmtter: Arc<Mutex<Json>>,
+    inner: Mutex<JsonProgressInner>,
+}
+
+struct JsonProgressInner {
+    state: ProgressState,
+    last_emitted: Option<Instant>,
+    /// Whether an update was skipped by the throttle since the last record was written.
+    pending: bool,
+}
+
+impl JsonProgress {
+    fn new(fmtter: Arc<Mutex<Json>>, total: u64, msg: &dyn Displayable) -> Self {
+        let progress = Self {
+            fmtter,
+            inner: Mutex::new(JsonProgressInner {
+                state: ProgressState::new(total, msg),
+                last_emitted: None,
+                pending: false,
+            }),
+        };
+
+        progress.emit(true);
+        progress
+    }
+
+    /// Writes out a progress record unless one was already written within the throttle window. `force` bypasses the
+    /// throttle.
+    fn emit(&self, force: bool) {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let throttled = inner
+            .last_emitted
+            .is_some_and(|last| last.elapsed() < PROGRESS_THROTTLE);
+        if throttled && !force {
+            inner.pending = true;
             return;
         }
 
-        let tmp = json!({
-            "label": "info",
-            "data": msg.as_serialize(),
-        });
+        inner.last_emitted = Some(Instant::now());
+        inner.pending = false;
+        let event = Event::Progress {
+            data: inner.state.message.clone(),
+            current: inner.state.position,
+            total: inner.state.total,
+        };
+        drop(inner);
 
-        let mut output_target = self.output_target.lock().unwrap();
+        let mut fmt = self.fmtter.lock().unwrap_or_else(PoisonError::into_inner);
+        let result = fmt.write_record(Destination::Output, event);
+        fmt.write_errors.handle(result);
+    }
+}
 
-        let _ = match serde_json::to_string(&tmp) {
-            Ok(s) => writeln!(output_target, "{s}"),
-            Err(e) => writeln!(output_target, "Error serializing to JSON: {e:?}"),
-        };
+impl Drop for JsonProgress {
+    fn drop(&mut self) {
+        let inner = self.inner.get_mut().unwrap_or_else(PoisonError::into_inner);
+        if inner.pending && !inner.state.finished {
+            self.emit(true);
+        }
     }
+}
 
-    fn println(&mut self, msg: &dyn Displayable) {
-        if !take_and_check_allowed(Format::Json, &mut self.allowed_formats) {
+impl ProgressHandle for JsonProgress {
+    fn inc(&self, delta: u64) {
+        self.inner.lock().unwrap().state.inc(delta);
+        self.emit(false);
+    }
+
+    fn set_position(&self, position: u64) {
+        self.inner.lock().unwrap().state.set_position(position);
+        self.emit(false);
+    }
+
+    fn set_message(&self, msg: &dyn Displayable) {
+        self.inner.lock().unwrap().state.message = msg.to_string();
+    }
+
+    fn finish(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state.finished {
             return;
         }
 
-        let tmp = json!({
-            "label": "info",
-            "data": msg.as_serialize(),
-        });
+        inner.state.finished = true;
+        inner.state.position = inner.state.total;
+        drop(inner);
 
-        let mut output_target = self.output_target.lock().unwrap();
+        self.emit(true);
+    }
+}
 
-        let _ = match serde_json::to_string(&tmp) {
-            Ok(s) => writeln!(output_target, "{s}"),
-            Err(e) => writeln!(output_target, "Error serializing to JSON: {e:?}"),
+/// Task handle which reports status changes as `task` records tagged with the task name.
+struct JsonTask {
+    fmtter: Arc<Mutex<Json>>,
+    name: String,
+    finished: Mutex<bool>,
+}
+
+impl JsonTask {
+    fn new(fmtter: Arc<Mutex<Json>>, name: &dyn Displayable) -> Self {
+        let task = Self {
+            fmtter,
+            name: name.to_string(),
+            finished: Mutex::new(false),
         };
+
+        task.emit(TaskStatus::Started, &task.name.as_str());
+        task
     }
 
-    fn error(&mut self, msg: &dyn Displayable) {
-        if !take_and_check_allowed(Format::Json, &mut self.allowed_formats) {
+    fn emit(&self, status: TaskStatus, msg: &dyn Displayable) {
+        let event = Event::Task {
+            task: self.name.clone(),
+            status,
+            data: to_value(msg),
+        };
+
+        let mut fmt = self.fmtter.lock().unwrap();
+        let result = fmt.write_record(Destination::Output, event);
+        fmt.write_errors.handle(result);
+    }
+
+    /// Emits the final status of the task, unless the task has already finished.
+    fn finish(&self, status: TaskStatus, msg: &dyn Displayable) {
+        let mut finished = self.finished.lock().unwrap();
+        if *finished {
             return;
         }
 
-        let tmp = json!({
-            "label": "error",
-            "data": msg.as_serialize(),
+        *finished = true;
+        self.emit(status, msg);
+    }
+}
+
+impl TaskHandle for JsonTask {
+    fn set_message(&self, msg: &dyn Displayable) {
+        if *self.finished.lock().unwrap() {
+            return;
+        }
+
+        self.emit(TaskStatus::Running, msg);
+    }
+
+    fn success(&self, msg: &dyn Displayable) {
+        self.finish(TaskStatus::Success, msg);
+    }
+
+    fn error(&self, msg: &dyn Displayable) {
+        self.finish(TaskStatus::Error, msg);
+    }
+}
+
+impl Json {
+    /// Takes the pending fields, preceded by any pending metadata they don't override.
+    fn take_fields(&mut self) -> Vec<Field> {
+        let fields = std::mem::take(&mut self.fields);
+        let mut merged = std::mem::take(&mut self.metadata);
+        merged.retain(|field| fields.iter().all(|other| other.key != field.key));
+        merged.extend(fields);
+        merged
+    }
+
+    /// Builds a single message, attaching any structured fields.
+    fn message(&self, msg: &dyn Displayable, fields: &[Field]) -> Message {
+        let fields = (!fields.is_empty()).then(|| {
+            fields
+                .iter()
+                .map(|field| (field.key.clone(), field.value.clone()))
+                .collect()
         });
 
-        let mut output_target = self.output_target.lock().unwrap();
+        Message {
+            data: to_value(msg),
+            fields,
+        }
+    }
 
-        let _ = match serde_json::to_string(&tmp) {
-            Ok(s) => writeln!(output_target, "{s}"),
-            Err(e) => writeln!(output_target, "Error serializing to JSON: {e:?}"),
-        };
+    /// Returns the target for records routed to the given destination.
+    fn target(&self, destination: Destination) -> &Arc<Mutex<dyn Write + Send>> {
+        match destination {
+            Destination::Output => &self.output_target,
+            Destination::Diagnostic => &self.diagnostic_target,
+        }
     }
 
-    fn success(&mut self, msg: &dyn Displayable) {
-        if !take_and_check_allowed(Format::Json, &mut self.allowed_formats) {
-            return;
+    /// Serializes a single record and writes it out as its own line, or holds on to it until the document is written
+    /// if the formatter collects its output.
+    ///
+    /// Every record is stamped with the schema version, the wall-clock time, the elapsed time in milliseconds, the
+    /// indentation depth and the ids of the groups it is nested in, so that consumers can rebuild the tree the Tree
+    /// format draws.
+    fn write_record(&mut self, destination: Destination, event: Event) -> io::Result<()> {
+        let record = Record {
+            schema_version: SCHEMA_VERSION,
+            event,
+            ts: self.clock.now(),
+            elapsed_ms: u64::try_from(self.clock.elapsed().as_millis()).unwrap_or(u64::MAX),
+            depth: self.groups.len(),
+            groups: self.groups.clone(),
+        };
+        let record = serde_json::to_value(record).map_err(io::Error::other)?;
+
+        if self.mode != JsonMode::Lines && destination == Destination::Output {
+            // Records printed after the document was written would start a second document on the output target,
+            // so they're written to the diagnostic target instead.
+            if self.document_written {
+                return self.write_value(Destination::Diagnostic, &record);
+            }
+
+            self.records.push(record);
+            return Ok(());
         }
 
-        let tmp = json!({
-            "label": "success",
-            "data": msg.as_serialize(),
-        });
+        self.write_value(destination, &record)
+    }
 
-        let mut output_target = self.output_target.lock().unwrap();
+    /// Encodes a value and writes it out: as a line of JSON, or as its own `---` separated YAML document.
+    fn write_value(&self, destination: Destination, value: &serde_json::Value) -> io::Result<()> {
+        // The document may be written while unwinding from a panic that happened mid-write, which must not turn into
+        // a second panic.
+        let mut output_target = self
+            .target(destination)
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if self.encoding == Encoding::Yaml {
+            return match serde_yaml::to_string(value) {
+                Ok(s) => write!(output_target, "---\n{s}"),
+                Err(e) => writeln!(output_target, "---\nError serializing to YAML: {e:?}"),
+            };
+        }
+
+        let serialized = if self.pretty {
+            serde_json::to_string_pretty(value)
+        } else {
+            serde_json::to_string(value)
+        };
 
-        let _ = match serde_json::to_string(&tmp) {
+        match serialized {
             Ok(s) => writeln!(output_target, "{s}"),
             Err(e) => writeln!(output_target, "Error serializing to JSON: {e:?}"),
+        }
+    }
+
+    /// Writes the records collected so far as a single document. Does nothing when records are written as lines or
+    /// the document has already been written.
+    fn write_document(&mut self) -> io::Result<()> {
+        if self.mode == JsonMode::Lines || self.document_written {
+            return Ok(());
+        }
+
+        let records = std::mem::take(&mut self.records);
+        self.document_written = true;
+
+        let document = match self.mode {
+            JsonMode::Document => {
+                let (results, messages): (Vec<_>, Vec<_>) = records
+                    .into_iter()
+                    .partition(|record| matches!(record["label"].as_str(), Some("info" | "table")));
+                json!({
+                    "results": results,
+                    "messages": messages,
+                })
+            }
+            _ => serde_json::Value::Array(records),
         };
+
+        self.write_value(Destination::Output, &document)
     }
 
-    fn warning(&mut self, msg: &dyn Displayable) {
-        if !take_and_check_allowed(Format::Json, &mut self.allowed_formats) {
-            return;
+    fn print(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let fields = self.take_fields();
+        if !take_and_check_allowed(self.encoding.format(), &mut self.allowed_formats)
+            || !self.verbosity.allows(Verbosity::Info)
+        {
+            return Ok(());
         }
 
-        let tmp = json!({
-            "label": "warning",
-            "data": msg.as_serialize(),
-        });
+        let event = Event::Info(self.message(msg, &fields));
 
-        let mut output_target = self.output_target.lock().unwrap();
+        self.write_record(Destination::Output, event)
+    }
 
-        let _ = match serde_json::to_string(&tmp) {
-            Ok(s) => writeln!(output_target, "{s}"),
-            Err(e) => writeln!(output_target, "Error serializing to JSON: {e:?}"),
-        };
+    fn println(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let fields = self.take_fields();
+        if !take_and_check_allowed(self.encoding.format(), &mut self.allowed_formats)
+            || !self.verbosity.allows(Verbosity::Info)
+        {
+            return Ok(());
+        }
+
+        let event = Event::Info(self.message(msg, &fields));
+
+        self.write_record(Destination::Output, event)
     }
 
-    fn debug(&mut self, msg: &dyn Displayable) {
-        if !take_and_check_allowed(Format::Json, &mut self.allowed_formats) || !self.debug {
-            return;
+    fn error(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let fields = self.take_fields();
+        if !take_and_check_allowed(self.encoding.format(), &mut self.allowed_formats) {
+            return Ok(());
         }
 
-        let tmp = json!({
-            "label": "debug",
-            "data": msg.as_serialize(),
-        });
+        let event = Event::Error(self.message(msg, &fields));
 
-        let mut output_target = self.output_target.lock().unwrap();
+        self.write_record(self.routing.error, event)
+    }
 
-        let _ = match serde_json::to_string(&tmp) {
-            Ok(s) => writeln!(output_target, "{s}"),
-            Err(e) => writeln!(output_target, "Error serializing to JSON: {e:?}"),
+    fn success(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let fields = self.take_fields();
+        if !take_and_check_allowed(self.encoding.format(), &mut self.allowed_formats)
+            || !self.verbosity.allows(Verbosity::Info)
+        {
+            return Ok(());
+        }
+
+        let event = Event::Success(self.message(msg, &fields));
+
+        self.write_record(self.routing.success, event)
+    }
+
+    fn warning(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let fields = self.take_fields();
+        if !take_and_check_allowed(self.encoding.format(), &mut self.allowed_formats)
+            || !self.verbosity.allows(Verbosity::Warn)
+        {
+            return Ok(());
+        }
+
+        let event = Event::Warning(self.message(msg, &fields));
+
+        self.write_record(self.routing.warning, event)
+    }
+
+    fn debug(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        self.leveled(Verbosity::Debug, msg)
+    }
+
+    fn trace(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        self.leveled(Verbosity::Trace, msg)
+    }
+
+    /// Prints debug and trace lines, which only differ in their label and the verbosity they need.
+    fn leveled(&mut self, level: Verbosity, msg: &dyn Displayable) -> io::Result<()> {
+        let fields = self.take_fields();
+        if !take_and_check_allowed(self.encoding.format(), &mut self.allowed_formats)
+            || !self.verbosity.allows(level)
+        {
+            return Ok(());
+        }
+
+        let message = self.message(msg, &fields);
+        let event = match level {
+            Verbosity::Trace => Event::Trace(message),
+            _ => Event::Debug(message),
         };
+
+        self.write_record(self.routing.debug, event)
     }
 
     fn indent(fmtter: &Arc<Mutex<Self>>) -> Box<dyn IndentGuard> {
+        let mut fmt = fmtter.lock().unwrap();
+        let group = fmt.next_group;
+        fmt.next_group += 1;
+        fmt.groups.push(group);
+        drop(fmt);
         let cloned_tree = Arc::clone(fmtter);
-        let guard = Guard::new(cloned_tree);
+        let guard = Guard::new(cloned_tree, group);
         Box::new(guard)
     }
 
-    fn outdent(&mut self) {}
+    fn progress(
+        fmtter: &Arc<Mutex<Self>>,
+        total: u64,
+        msg: &dyn Displayable,
+    ) -> Box<dyn ProgressHandle> {
+        let mut fmt = fmtter.lock().unwrap();
+        fmt.fields.clear();
+        fmt.metadata.clear();
+        if !take_and_check_allowed(fmt.encoding.format(), &mut fmt.allowed_formats)
+            || !fmt.verbosity.allows(Verbosity::Info)
+        {
+            return Box::new(NoopProgress);
+        }
+        drop(fmt);
+
+        Box::new(JsonProgress::new(Arc::clone(fmtter), total, msg))
+    }
+
+    fn task(fmtter: &Arc<Mutex<Self>>, name: &dyn Displayable) -> Box<dyn TaskHandle> {
+        let mut fmt = fmtter.lock().unwrap();
+        fmt.fields.clear();
+        fmt.metadata.clear();
+        if !take_and_check_allowed(fmt.encoding.format(), &mut fmt.allowed_formats)
+            || !fmt.verbosity.allows(Verbosity::Info)
+        {
+            return Box::new(NoopTask);
+        }
+        drop(fmt);
+
+        Box::new(JsonTask::new(Arc::clone(fmtter), name))
+    }
+
+    fn outdent(&mut self) {
+        self.groups.pop();
+    }
 
-    fn spacer(&mut self) {}
+    /// Closes the given group wherever it is, unlike [`outdent`](Self::outdent) which closes the innermost one.
+    fn close_group(&mut self, group: u64) {
+        self.groups.retain(|open| *open != group);
+    }
+
+    fn spacer(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn table(&mut self, headers: &[&str], rows: &[Vec<String>]) -> io::Result<()> {
+        self.fields.clear();
+        self.metadata.clear();
+        if !take_and_check_allowed(self.encoding.format(), &mut self.allowed_formats)
+            || !self.verbosity.allows(Verbosity::Info)
+        {
+            return Ok(());
+        }
+
+        let data = rows
+            .iter()
+            .map(|row| {
+                headers
+                    .iter()
+                    .enumerate()
+                    .map(|(index, header)| {
+                        let cell = row.get(index).cloned().unwrap_or_default();
+                        (header.to_string(), cell)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        self.write_record(Destination::Output, Event::Table { data })
+    }
 
     #[allow(dead_code)]
     fn pause(&mut self) {}
@@ -168,31 +563,43 @@ impl Json {
     #[allow(dead_code)]
     fn start(&mut self) {}
 
-    fn question(&mut self, msg: &dyn Displayable) -> String {
-        if !take_and_check_allowed(Format::Json, &mut self.allowed_formats) {
-            return "".to_string();
-        }
+    fn question(&mut self, msg: &dyn Displayable) -> io::Result<String> {
+        self.ask(msg, &mut io::stdin().lock())
+    }
 
-        let tmp = json!({
-            "label": "question",
-            "data": msg.as_serialize(),
-        });
+    /// Writes the question and reads the answer from `input`.
+    pub(crate) fn ask(
+        &mut self,
+        msg: &dyn Displayable,
+        input: &mut dyn BufRead,
+    ) -> io::Result<String> {
+        let fields = self.take_fields();
+        if !take_and_check_allowed(self.encoding.format(), &mut self.allowed_formats) {
+            return Ok("".to_string());
+        }
 
-        let mut output_target = self.output_target.lock().unwrap();
+        let event = Event::Question(self.message(msg, &fields));
 
-        let _ = match serde_json::to_string(&tmp) {
-            Ok(s) => writeln!(output_target, "{s}"),
-            Err(e) => writeln!(output_target, "Error serializing to JSON: {e:?}"),
+        // The answer is read right away, so the prompt can't wait for the collected document to be written. It's
+        // written to the diagnostic target as its own record instead.
+        let destination = match self.mode {
+            JsonMode::Lines => self.routing.question,
+            JsonMode::Array | JsonMode::Document => Destination::Diagnostic,
         };
+        self.write_record(destination, event)?;
 
-        output_target.flush().unwrap();
+        let mut output_target = self
+            .target(destination)
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        output_target.flush()?;
         drop(output_target);
 
-        let mut input = String::from("");
+        let mut answer = String::from("");
 
-        let _ = std::io::stdin().read_line(&mut input);
+        input.read_line(&mut answer)?;
 
-        input.trim().to_string()
+        Ok(answer.trim().to_string())
     }
 
     fn only(&mut self, types: Vec<Format>) -> &mut Self {
@@ -200,8 +607,48 @@ impl Json {
         self
     }
 
-    fn finish(&self) {
-        if let Ok(mut out) = self.output_target.lock() {
+    fn with_fields(&mut self, fields: &[(&str, &dyn Displayable)]) -> &mut Self {
+        self.fields = fields
+            .iter()
+            .map(|(key, value)| Field::new(key, *value))
+            .collect();
+        self
+    }
+
+    fn with_metadata(&mut self, fields: &[(&str, &dyn Displayable)]) -> &mut Self {
+        self.metadata = fields
+            .iter()
+            .map(|(key, value)| Field::new(key, *value))
+            .collect();
+        self
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.write_document()?;
+
+        for target in [&self.output_target, &self.diagnostic_target] {
+            if let Ok(mut out) = target.lock() {
+                out.flush()?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Serializes a message into the value written as its `data`, falling back to its text if it can't be serialized.
+fn to_value(msg: &dyn Displayable) -> serde_json::Value {
+    serde_json::to_value(msg.as_serialize())
+        .unwrap_or_else(|_| serde_json::Value::String(msg.to_string()))
+}
+
+impl Drop for Json {
+    /// Writes out any collected records which weren't written by [`finish`](Formatter::finish), so that a program
+    /// returning early still leaves a complete document behind.
+    fn drop(&mut self) {
+        if self.write_document().is_ok()
+            && let Ok(mut out) = self.output_target.lock()
+        {
             let _ = out.flush();
         }
     }
@@ -210,38 +657,58 @@ impl Json {
 impl Formatter for Arc<Mutex<Json>> {
     fn print(&mut self, msg: &dyn Displayable) {
         let mut fmt = self.lock().unwrap();
-        fmt.print(msg);
+        let result = fmt.print(msg);
+        fmt.write_errors.handle(result);
     }
 
     fn println(&mut self, msg: &dyn Displayable) {
         let mut fmt = self.lock().unwrap();
-        fmt.println(msg);
+        let result = fmt.println(msg);
+        fmt.write_errors.handle(result);
     }
 
     fn error(&mut self, msg: &dyn Displayable) {
         let mut fmt = self.lock().unwrap();
-        fmt.error(msg);
+        let result = fmt.error(msg);
+        fmt.write_errors.handle(result);
     }
 
     fn success(&mut self, msg: &dyn Displayable) {
         let mut fmt = self.lock().unwrap();
-        fmt.success(msg);
+        let result = fmt.success(msg);
+        fmt.write_errors.handle(result);
     }
 
     fn warning(&mut self, msg: &dyn Displayable) {
         let mut fmt = self.lock().unwrap();
-        fmt.warning(msg);
+        let result = fmt.warning(msg);
+        fmt.write_errors.handle(result);
     }
 
     fn debug(&mut self, msg: &dyn Displayable) {
         let mut fmt = self.lock().unwrap();
-        fmt.debug(msg);
+        let result = fmt.debug(msg);
+        fmt.write_errors.handle(result);
+    }
+
+    fn trace(&mut self, msg: &dyn Displayable) {
+        let mut fmt = self.lock().unwrap();
+        let result = fmt.trace(msg);
+        fmt.write_errors.handle(result);
     }
 
     fn indent(&mut self) -> Box<dyn IndentGuard> {
         Json::indent(self)
     }
 
+    fn progress(&mut self, total: u64, msg: &dyn Displayable) -> Box<dyn ProgressHandle> {
+        Json::progress(self, total, msg)
+    }
+
+    fn task(&mut self, name: &dyn Displayable) -> Box<dyn TaskHandle> {
+        Json::task(self, name)
+    }
+
     fn outdent(&mut self) {
         let mut fmt = self.lock().unwrap();
         fmt.outdent();
@@ -249,7 +716,14 @@ impl Formatter for Arc<Mutex<Json>> {
 
     fn spacer(&mut self) {
         let mut fmt = self.lock().unwrap();
-        fmt.spacer();
+        let result = fmt.spacer();
+        fmt.write_errors.handle(result);
+    }
+
+    fn table(&mut self, headers: &[&str], rows: &[Vec<String>]) {
+        let mut fmt = self.lock().unwrap();
+        let result = fmt.table(headers, rows);
+        fmt.write_errors.handle(result);
     }
 
     fn pause(&mut self) {}
@@ -258,7 +732,8 @@ impl Formatter for Arc<Mutex<Json>> {
 
     fn question(&mut self, msg: &dyn Displayable) -> String {
         let mut fmt = self.lock().unwrap();
-        fmt.question(msg)
+        let result = fmt.question(msg);
+        fmt.write_errors.handle(result)
     }
 
     fn only(&mut self, types: Vec<Format>) -> &mut dyn Formatter {
@@ -268,8 +743,83 @@ impl Formatter for Arc<Mutex<Json>> {
         self
     }
 
+    fn with_fields(&mut self, fields: &[(&str, &dyn Displayable)]) -> &mut dyn Formatter {
+        let mut fmt = self.lock().unwrap();
+        fmt.with_fields(fields);
+        drop(fmt);
+        self
+    }
+
+    fn with_metadata(&mut self, fields: &[(&str, &dyn Displayable)]) -> &mut dyn Formatter {
+        let mut fmt = self.lock().unwrap();
+        fmt.with_metadata(fields);
+        drop(fmt);
+        self
+    }
+
+    fn format(&self) -> Format {
+        self.lock().unwrap().encoding.format()
+    }
+
     fn finish(&self) {
-        let fmt = self.lock().unwrap();
-        fmt.finish();
+        let mut fmt = self.lock().unwrap();
+        let result = fmt.finish();
+        fmt.write_errors.handle(result);
+    }
+
+    fn try_print(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.print(msg)
+    }
+
+    fn try_println(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.println(msg)
+    }
+
+    fn try_error(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.error(msg)
+    }
+
+    fn try_success(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.success(msg)
+    }
+
+    fn try_warning(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.warning(msg)
+    }
+
+    fn try_debug(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.debug(msg)
+    }
+
+    fn try_trace(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.trace(msg)
+    }
+
+    fn try_spacer(&mut self) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.spacer()
+    }
+
+    fn try_table(&mut self, headers: &[&str], rows: &[Vec<String>]) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.table(headers, rows)
+    }
+
+    fn try_question(&mut self, msg: &dyn Displayable) -> io::Result<String> {
+        let mut fmt = self.lock().unwrap();
+        fmt.question(msg)
+    }
+
+    fn try_finish(&self) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        let result = fmt.finish();
+        fmt.write_errors.take().and(result)
     }
 }
@@ -93,6 +93,16 @@
 //! let mut fmt = new(format, Options::default());
 //! ```
 //!
+//! If you'd rather not decide at all, [`Format::Auto`] inspects the environment and picks a format for you: a
+//! spinner on an interactive terminal, plain text when piped, and JSON when running under CI. Users can always
+//! override the choice with the `POLYFMT_FORMAT` environment variable.
+//!
+//! ```rust
+//! # use polyfmt::{new, Format, Options};
+//! let fmt = new(Format::Auto, Options::default());
+//! assert_ne!(fmt.format(), Format::Auto);
+//! ```
+//!
 //! ### Tuning `Options`
 //!
 //! `Options::default()` gets you sensible defaults (no debug output, auto max line length based on terminal width,
@@ -108,6 +118,8 @@
 //!
 //! Builder helpers:
 //! * [`Options::with_debug`] — enable/disable debug lines (default: off).
+//! * [`Options::with_verbosity`] — pick which messages print, from [`Verbosity::Error`] to [`Verbosity::Trace`]
+//!   (default: [`Verbosity::Info`]). [`Verbosity::from_flags`] maps `-q`/`-v` counts to a level.
 //! * [`Options::with_max_line_length`] — override wrapping length (default: terminal width minus a margin).
 //! * [`Options::with_padding`] — add leading spaces (default: 0).
 //! * [`Options::with_custom_output_target`] — send output to any `Write + Send + 'static` target (files, buffers,
@@ -115,6 +127,20 @@
 //!
 //! Note: Spinner falls back to plain when using a custom target because spinners only make sense on a TTY.
 //!
+//! ### Configuring from the environment
+//!
+//! [`Options::from_env`] and [`Format::from_env`] read the format, verbosity, color mode, max line length, padding
+//! and an output file from variables named after your program (`MYAPP_FORMAT`, `MYAPP_VERBOSITY`, `MYAPP_COLOR`,
+//! ...). Invalid values come back as an [`EnvError`] naming the variable.
+//!
+//! ```rust
+//! # use polyfmt::{new, Format, Options};
+//! let format = Format::from_env("MYAPP")?;
+//! let options = Options::from_env("MYAPP")?.with_padding(2);
+//! let mut fmt = new(format, options);
+//! # Ok::<(), polyfmt::EnvError>(())
+//! ```
+//!
 //! ### Redirecting output (stdout, files, buffers)
 //!
 //! By default polyfmt writes to stdout (line-buffered). You can point output at any `Write + Send + Sync`
@@ -134,6 +160,165 @@
 //! The spinner formatter only makes sense on a TTY; if you request [`Format::Spinner`] with a custom output target,
 //! polyfmt will fall back to the plain formatter.
 //!
+//! ### Sending diagnostics to stderr
+//!
+//! By default every message is written to the output target. To keep errors and warnings out of piped output, route
+//! them to the diagnostic target (stderr unless changed with [`Options::with_custom_diagnostic_target`]) with
+//! [`Options::with_routing`]. Regular output from `print`, `println` and tables always stays on the output target.
+//!
+//! ```rust
+//! # use polyfmt::{new, Format, Options, Routing};
+//! let opts = Options::default().with_routing(Routing::diagnostics());
+//! let mut fmt = new(Format::Plain, opts);
+//! fmt.println(&"written to stdout");
+//! fmt.error(&"written to stderr");
+//! ```
+//!
+//! The spinner format keeps output lines above the spinner and writes diagnostics with the spinner cleared.
+//!
+//! ### Handling write errors
+//!
+//! The regular formatter methods never fail. What they do when writing to the output target fails (a full disk, a
+//! closed socket) is decided by [`Options::with_write_error_policy`]: ignore the error (the default), record it so
+//! that [`try_finish`](Formatter::try_finish) can return it, or panic. Every printing method also has a `try_`
+//! counterpart which returns the error directly.
+//!
+//! ```rust
+//! # use polyfmt::{new, Format, Options, WriteErrorPolicy};
+//! let opts = Options::default().with_write_error_policy(WriteErrorPolicy::Record);
+//! let mut fmt = new(Format::Plain, opts);
+//! fmt.try_println(&"written right away")?;
+//! fmt.println(&"errors are recorded");
+//! fmt.try_finish()?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! ### Broken pipes
+//!
+//! When output is piped into a program that exits early, like `head`, polyfmt notices the closed pipe and silently
+//! discards everything written afterwards instead of failing on every call. Command line tools usually exit at that
+//! point; opt into that with [`Options::with_exit_on_broken_pipe`], which exits with [`BROKEN_PIPE_EXIT_STATUS`].
+//!
+//! ### Structured fields
+//!
+//! Rather than stringifying context into the message you can attach it as key/value fields with
+//! [with_fields](Formatter::with_fields). Human readable formats print the fields as dimmed `key=value` pairs
+//! after the message and the json format adds a `fields` object to the record.
+//!
+//! ```rust
+//! # use polyfmt::{new, error, Format, Options};
+//! # let mut fmt = polyfmt::new(Format::Plain, Options::default());
+//! fmt.with_fields(&[("id", &42), ("region", &"us")]).error(&"failed to fetch");
+//!
+//! // The macros accept fields after a semicolon.
+//! error!("failed to fetch"; id = 42, region = "us");
+//! ```
+//!
+//! ### Tables
+//!
+//! Lists of resources can be printed with [table](Formatter::table). Human readable formats align the columns
+//! (shrinking them to fit the line length), the tree format draws a box around them and the json format emits the
+//! rows as an array of objects keyed by header.
+//!
+//! ```rust
+//! # use polyfmt::{new, Format, Options};
+//! # let mut fmt = polyfmt::new(Format::Plain, Options::default());
+//! fmt.table(
+//!     &["NAME", "STATUS"],
+//!     &[vec!["api".to_string(), "running".to_string()]],
+//! );
+//! ```
+//!
+//! ### Progress
+//!
+//! Long running work with a known size can report progress through [progress](Formatter::progress). The returned
+//! [`ProgressHandle`] is shared across threads and drawn as a real progress bar by the spinner formatter.
+//!
+//! ```rust
+//! # use polyfmt::{new, Format, Options};
+//! # let mut fmt = polyfmt::new(Format::Plain, Options::default());
+//! let progress = fmt.progress(120, &"Downloading");
+//! progress.inc(37);
+//! progress.finish();
+//! ```
+//!
+//! ### Tasks
+//!
+//! When work happens in parallel, each unit of work can get its own [`TaskHandle`] through
+//! [task](Formatter::task). Tasks can be moved into threads and succeed or fail independently.
+//!
+//! ```rust
+//! # use polyfmt::{new, Format, Options};
+//! # let mut fmt = polyfmt::new(Format::Plain, Options::default());
+//! let task = fmt.task(&"build api");
+//! std::thread::spawn(move || {
+//!     task.set_message(&"compiling");
+//!     task.success(&"built");
+//! })
+//! .join()
+//! .unwrap();
+//! ```
+//!
+//! ### Writing to multiple formatters
+//!
+//! A [`Tee`] fans every call out to several formatters, for example pretty output on the terminal and a JSON log
+//! in a file. Filters set with [only](Formatter::only) are checked against each child's own format.
+//!
+//! ```rust
+//! # use polyfmt::{new, Format, Formatter, Options, Tee};
+//! let log = Options::default().with_custom_output_target(Vec::new());
+//! let mut fmt = Tee::new(vec![new(Format::Plain, Options::default()), new(Format::Json, log)]);
+//! fmt.println(&"written to both");
+//! ```
+//!
+//! ### Themes
+//!
+//! The glyphs and colors drawn by the human readable formats come from a [`Theme`], set with
+//! [`Options::with_theme`]. Polyfmt ships with [`Theme::default`], [`Theme::ascii`], [`Theme::high_contrast`] and
+//! [`Theme::monochrome`]; override individual [glyphs](Glyph) to match your brand.
+//!
+//! By default polyfmt picks [`Theme::ascii`] when the locale or `TERM` suggests the terminal can't render Unicode
+//! (see [`Theme::detect`]). Use [`Options::with_ascii`] to force either choice.
+//!
+//! ```rust
+//! # use polyfmt::{new, Color, Format, Glyph, Options, Theme};
+//! let theme = Theme {
+//!     success: Glyph::new("✔").color(Color::TrueColor { r: 0, g: 200, b: 150 }).bold(),
+//!     ..Theme::high_contrast()
+//! };
+//! let mut fmt = new(Format::Plain, Options::default().with_theme(theme));
+//! fmt.success(&"deployed");
+//! ```
+//!
+//! ### Links
+//!
+//! A [`Link`] pairs text with a URL. Plain, Tree and Spinner print it as a clickable hyperlink in terminals that
+//! support them and as `text (url)` otherwise, while Json writes it as an object with `text` and `url` keys.
+//!
+//! ```rust
+//! # use polyfmt::{new, Format, Link, Options};
+//! let mut fmt = new(Format::Plain, Options::default());
+//! fmt.success(&Link::new("deployment finished", "https://dash.example.com/deploys/42"));
+//! ```
+//!
+//! ### Testing
+//!
+//! The [testing] module provides [`Capture`](testing::Capture), a formatter which records every call as a typed event
+//! instead of printing it. Tests can assert on the recorded messages, indentation and fields directly, or render the
+//! recording through any real format for snapshot comparison.
+//!
+//! ```rust
+//! # use polyfmt::{Format, Formatter, Options};
+//! use polyfmt::testing::{Capture, EventKind};
+//!
+//! let capture = Capture::new();
+//! let mut fmt: Box<dyn Formatter> = Box::new(capture.clone());
+//! fmt.success(&"deployed");
+//!
+//! capture.assert_messages(&[(EventKind::Success, "deployed")]);
+//! assert!(capture.render(Format::Json, Options::default()).contains("\"label\":\"success\""));
+//! ```
+//!
 //! ### Indentation
 //! Polyfmt supports indentation also with a similar implementation to spans in the tracing crate
 //! You initialize the indent, tie it to a guard, and then once that guard drops out of scope the
@@ -156,15 +341,39 @@
 //!   plaintext and json.
 //! * When you finish using a formatter you should call the [finish](Formatter::finish) function. This flushes the output
 //!   buffer and cleans up anything else before your program exists.
+//! * Json records follow the types in [`schema`], versioned by [`schema::SCHEMA_VERSION`], and can be validated with
+//!   the JSON Schema returned by [`schema::json_schema`].
+//! * Feature `clap` provides [`cli::OutputArgs`], the standard `--format`, `--no-color`, `--debug`, `--quiet` and
+//!   `--verbose` flags, ready to be flattened into a clap parser.
+//! * Feature `log` provides [`log::init`], which routes records from the `log` facade through the global formatter.
+//! * Feature `tracing` provides [`tracing::PolyfmtLayer`], a `tracing_subscriber` layer which writes events through
+//!   a formatter and turns spans into indentation.
 //! * Feature `tui` enables interactive helpers such as `polyfmt::tui::choose_one` and `choose_many`; they draw directly
 //!   to stdout/tty and are not suitable for custom output targets.
 //!
 
+mod ansi;
+#[cfg(feature = "clap")]
+pub mod cli;
+mod drain;
+mod env;
 mod json;
+mod link;
+#[cfg(feature = "log")]
+pub mod log;
 pub mod macros;
 mod plain;
+mod progress;
+pub mod schema;
 mod silent;
 mod spinner;
+mod task;
+mod tee;
+pub mod testing;
+mod theme;
+mod timestamp;
+#[cfg(feature = "tracing")]
+pub mod tracing;
 mod tree;
 #[cfg(feature = "tui")]
 pub mod tui;
@@ -175,14 +384,26 @@ use serde::{Deserialize, Serialize};
 use std::{
     collections::HashSet,
     fmt::{Debug, Display},
-    io::Write,
+    io::{self, IsTerminal, Write},
+    str::FromStr,
     sync::{Arc, Mutex},
     time::Duration,
 };
-use strum::EnumString;
-
-#[derive(Debug, Default, EnumString, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[strum(ascii_case_insensitive)]
+use strum::{EnumString, EnumVariantNames};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+pub use colored::Color;
+pub use drain::BROKEN_PIPE_EXIT_STATUS;
+pub use env::EnvError;
+pub use link::Link;
+pub use tee::Tee;
+pub use theme::{Borders, Glyph, Theme};
+
+#[derive(
+    Debug, Default, EnumString, EnumVariantNames, Clone, PartialEq, Eq, Hash, Serialize, Deserialize,
+)]
+#[strum(ascii_case_insensitive, serialize_all = "lowercase")]
 pub enum Format {
     #[default]
     /// Outputs text in a humanized fashion without any other additions.
@@ -198,14 +419,179 @@ pub enum Format {
     /// Outputs json formatted text, mainly suitable to be read by computers.
     Json,
 
+    /// Outputs each message as its own yaml document, structured like [`Json`](Format::Json) but easier to read
+    /// for humans.
+    Yaml,
+
     /// Dummy formatter that doesn't print anything, can be used when users don't want any
     /// output at all.
     Silent,
+
+    /// Picks one of the formats above based on the environment the program is running in.
+    ///
+    /// In order of precedence: the `POLYFMT_FORMAT` environment variable, [`Json`](Format::Json) when a well-known
+    /// CI variable is set, [`Plain`](Format::Plain) when the output target isn't a terminal or the terminal is
+    /// limited (`NO_COLOR` or `TERM=dumb`), and [`Spinner`](Format::Spinner) otherwise.
+    Auto,
+}
+
+/// How much the formatters print. Every level includes the levels above it, so [`Verbosity::Warn`] prints errors and
+/// warnings while [`Verbosity::Trace`] prints everything.
+///
+/// Errors and question prompts are always printed. Regular output ([`println`](Formatter::println),
+/// [`success`](Formatter::success), tables, progress bars and tasks) is printed from [`Verbosity::Info`] onward.
+#[derive(
+    Debug,
+    Default,
+    EnumString,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+#[strum(ascii_case_insensitive)]
+#[serde(rename_all = "lowercase")]
+pub enum Verbosity {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Verbosity {
+    /// Maps the number of times `-q`/`--quiet` and `-v`/`--verbose` were passed on the command line to a level,
+    /// starting from [`Verbosity::Info`]. Each `-v` raises the level by one and each `-q` lowers it, so `-q` is
+    /// [`Warn`](Verbosity::Warn), `-qq` is [`Error`](Verbosity::Error), `-v` is [`Debug`](Verbosity::Debug) and
+    /// `-vv` is [`Trace`](Verbosity::Trace).
+    ///
+    /// ```rust
+    /// # use polyfmt::Verbosity;
+    /// assert_eq!(Verbosity::from_flags(0, 2), Verbosity::Trace);
+    /// assert_eq!(Verbosity::from_flags(1, 0), Verbosity::Warn);
+    /// ```
+    pub fn from_flags(quiet: u8, verbose: u8) -> Self {
+        match i16::from(verbose) - i16::from(quiet) {
+            ..=-2 => Verbosity::Error,
+            -1 => Verbosity::Warn,
+            0 => Verbosity::Info,
+            1 => Verbosity::Debug,
+            2.. => Verbosity::Trace,
+        }
+    }
+
+    /// Returns true if messages at `level` are printed at this verbosity.
+    pub fn allows(self, level: Verbosity) -> bool {
+        level <= self
+    }
+}
+
+/// Environment variables set by common CI providers. If any of these are present [`Format::Auto`] resolves to
+/// [`Format::Json`].
+const CI_ENV_VARS: &[&str] = &[
+    "CI",
+    "CONTINUOUS_INTEGRATION",
+    "BUILD_NUMBER",
+    "GITHUB_ACTIONS",
+    "GITLAB_CI",
+    "CIRCLECI",
+    "TRAVIS",
+    "BUILDKITE",
+    "JENKINS_URL",
+    "TEAMCITY_VERSION",
+    "TF_BUILD",
+];
+
+impl Format {
+    /// Resolves [`Format::Auto`] into a concrete format for the given output target. Any other format is
+    /// returned unchanged.
+    pub fn resolve(self, target: &OutputTarget) -> Format {
+        if self != Format::Auto {
+            return self;
+        }
+
+        let is_terminal =
+            target.kind == OutputTargetKind::Stdout && std::io::stdout().is_terminal();
+
+        resolve_auto_format(is_terminal, |key| std::env::var(key).ok())
+    }
+}
+
+/// Decides which format [`Format::Auto`] should become. The environment is passed in as a lookup function so that
+/// the decision can be made without touching the real process environment.
+fn resolve_auto_format(is_terminal: bool, env: impl Fn(&str) -> Option<String>) -> Format {
+    if let Some(value) = env("POLYFMT_FORMAT") {
+        match Format::from_str(value.trim()) {
+            Ok(Format::Auto) | Err(_) => {}
+            Ok(format) => return format,
+        }
+    }
+
+    let in_ci = CI_ENV_VARS.iter().any(|key| {
+        env(key).is_some_and(|value| {
+            let value = value.trim();
+            !value.is_empty() && value != "0" && !value.eq_ignore_ascii_case("false")
+        })
+    });
+
+    if in_ci {
+        return Format::Json;
+    }
+
+    if !is_terminal {
+        return Format::Plain;
+    }
+
+    if env("NO_COLOR").is_some_and(|value| !value.is_empty())
+        || env("TERM").as_deref() == Some("dumb")
+    {
+        return Format::Plain;
+    }
+
+    Format::Spinner
 }
 
 /// Trait for the indentation guard.
 pub trait IndentGuard: Send + Sync {}
 
+/// Handle to a determinate progress indicator created by [`Formatter::progress`].
+///
+/// The handle can be shared across threads; every method takes `&self`.
+pub trait ProgressHandle: Send + Sync {
+    /// Advances the progress by `delta` units.
+    fn inc(&self, delta: u64);
+
+    /// Sets the progress to an absolute position. Positions past the total are clamped to the total.
+    fn set_position(&self, position: u64);
+
+    /// Replaces the message displayed alongside the progress.
+    fn set_message(&self, msg: &dyn Displayable);
+
+    /// Marks the progress as complete.
+    fn finish(&self);
+}
+
+/// Handle to a single concurrent task created by [`Formatter::task`].
+///
+/// Each task reports its own status independently of any other task, which makes it suitable for work being done
+/// in parallel threads. Once a task has succeeded or failed further updates are ignored.
+pub trait TaskHandle: Send + Sync {
+    /// Updates the status message of the task.
+    fn set_message(&self, msg: &dyn Displayable);
+
+    /// Marks the task as successfully finished.
+    fn success(&self, msg: &dyn Displayable);
+
+    /// Marks the task as failed.
+    fn error(&self, msg: &dyn Displayable);
+}
+
 /// We use a compound type here because we need to know if this is a standard stdout writer or a custom writer. This
 /// helps us decide which formatters are reasonable to use. (For example we would never use the spinner formatter when
 /// not writing to stdout)
@@ -218,13 +604,188 @@ pub struct OutputTarget {
 #[derive(Clone, PartialEq, Eq)]
 pub enum OutputTargetKind {
     Stdout,
+    Stderr,
     Custom,
 }
 
+impl OutputTarget {
+    fn stdout() -> Self {
+        Self {
+            kind: OutputTargetKind::Stdout,
+            // We default to writing to stdout, but we wrap it in a LineWriter so we consistently flush the buffer
+            // on newlines. This makes it so write buffering is more predictable.
+            target: Arc::new(Mutex::new(std::io::LineWriter::new(std::io::stdout()))),
+        }
+    }
+
+    fn stderr() -> Self {
+        Self {
+            kind: OutputTargetKind::Stderr,
+            target: Arc::new(Mutex::new(std::io::LineWriter::new(std::io::stderr()))),
+        }
+    }
+
+    fn custom<W: Write + Send + 'static>(target: W) -> Self {
+        Self {
+            kind: OutputTargetKind::Custom,
+            target: Arc::new(Mutex::new(std::io::LineWriter::new(target))),
+        }
+    }
+}
+
+/// Which target a kind of message gets written to. See [`Routing`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Destination {
+    /// The regular [output target](Options::output_target).
+    #[default]
+    Output,
+
+    /// The [diagnostic target](Options::diagnostic_target), stderr by default.
+    Diagnostic,
+}
+
+/// Decides which target each kind of message is written to. Regular output (`print`, `println`, `spacer`, tables and
+/// progress lines) always goes to the output target so that it can be safely piped into other programs.
+///
+/// By default everything goes to the output target. [`Routing::diagnostics`] sends errors, warnings, debug lines and
+/// question prompts to the diagnostic target instead. Trace lines follow the routing of debug lines.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Routing {
+    pub error: Destination,
+    pub success: Destination,
+    pub warning: Destination,
+    pub debug: Destination,
+    pub question: Destination,
+}
+
+impl Routing {
+    /// Routes errors, warnings, debug lines and question prompts to the diagnostic target. Success messages stay on
+    /// the output target.
+    pub fn diagnostics() -> Self {
+        Self {
+            error: Destination::Diagnostic,
+            success: Destination::Output,
+            warning: Destination::Diagnostic,
+            debug: Destination::Diagnostic,
+            question: Destination::Diagnostic,
+        }
+    }
+}
+
+/// Whether the human readable formatters print colors.
+#[derive(Debug, Default, EnumString, Clone, Copy, PartialEq, Eq, Hash)]
+#[strum(ascii_case_insensitive)]
+pub enum ColorMode {
+    /// Leave it to [colored](https://docs.rs/colored), which turns colors off when stdout isn't a terminal or
+    /// `NO_COLOR` is set, unless the program overrides it with `colored::control::set_override`.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    /// Whether a formatter created with this mode prints colors.
+    pub(crate) fn enabled(self) -> bool {
+        match self {
+            ColorMode::Auto => colored::control::SHOULD_COLORIZE.should_colorize(),
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
+    }
+}
+
+/// Whether the human readable formatters print a timestamp in front of each line.
+///
+/// [`Json`](Format::Json) records always include both the wall-clock time as `ts` and the elapsed time as
+/// `elapsed_ms`, whatever this is set to.
+#[derive(Debug, Default, EnumString, Clone, Copy, PartialEq, Eq, Hash)]
+#[strum(ascii_case_insensitive)]
+pub enum Timestamps {
+    #[default]
+    Off,
+
+    /// The wall-clock time in UTC as an RFC 3339 timestamp, like `2024-05-01T09:30:00.123Z`.
+    Rfc3339,
+
+    /// Seconds since the first formatter was created, which is usually close to when the program started.
+    Elapsed,
+}
+
+/// How the [`Json`](Format::Json) formatter lays out its records. [`Yaml`](Format::Yaml) lays them out the same way,
+/// writing YAML documents instead.
+#[derive(Debug, Default, EnumString, Clone, Copy, PartialEq, Eq, Hash)]
+#[strum(ascii_case_insensitive)]
+pub enum JsonMode {
+    /// Writes every record as soon as it is printed, one JSON value per line. Suitable for streaming into tools like
+    /// `jq`.
+    #[default]
+    Lines,
+
+    /// Collects the records and writes them as a single JSON array.
+    Array,
+
+    /// Collects the records and writes a single object which splits them into `results`, holding the regular
+    /// output (`info` and `table` records), and `messages`, holding everything else.
+    Document,
+}
+
+/// What the infallible [`Formatter`] methods do when writing to a target fails (for example because the disk is full
+/// or a socket was closed).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WriteErrorPolicy {
+    /// Drop the error and carry on.
+    #[default]
+    Ignore,
+
+    /// Keep the most recent error around so that it can be retrieved with [`Formatter::try_finish`].
+    Record,
+
+    /// Panic with the error.
+    Panic,
+}
+
+/// Applies a [`WriteErrorPolicy`] to the results of the infallible formatter methods.
+#[derive(Debug, Default)]
+pub(crate) struct WriteErrors {
+    policy: WriteErrorPolicy,
+    last: Option<io::Error>,
+}
+
+impl WriteErrors {
+    pub fn new(policy: WriteErrorPolicy) -> Self {
+        Self { policy, last: None }
+    }
+
+    /// Unwraps the result of a write, handling any error according to the policy. On error the default value is
+    /// returned in its place.
+    pub fn handle<T: Default>(&mut self, result: io::Result<T>) -> T {
+        match result {
+            Ok(value) => value,
+            Err(err) => {
+                match self.policy {
+                    WriteErrorPolicy::Ignore => {}
+                    WriteErrorPolicy::Record => self.last = Some(err),
+                    WriteErrorPolicy::Panic => panic!("polyfmt failed to write output: {err}"),
+                }
+                T::default()
+            }
+        }
+    }
+
+    /// Returns the recorded error, if any, and forgets it.
+    pub fn take(&mut self) -> io::Result<()> {
+        match self.last.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Options {
-    /// Turn on printing for debug lines. Defaults to false.
-    pub debug: bool,
+    /// Which messages are printed. Defaults to [`Verbosity::Info`].
+    pub verbosity: Verbosity,
 
     /// Maximum character length for lines including indentation. Defaults to terminal width.
     pub max_line_length: usize,
@@ -234,12 +795,66 @@ pub struct Options {
 
     /// Where all output is written. (e.g. `File`, `BufWriter`, `Cursor<Vec<u8>>`, etc). Defaults to stdout.
     pub output_target: OutputTarget,
+
+    /// Where messages routed to [`Destination::Diagnostic`] are written. Defaults to stderr.
+    pub diagnostic_target: OutputTarget,
+
+    /// Which target each kind of message is written to. Defaults to writing everything to the output target.
+    pub routing: Routing,
+
+    /// What the infallible formatter methods do when a write fails. Defaults to [`WriteErrorPolicy::Ignore`].
+    pub write_error_policy: WriteErrorPolicy,
+
+    /// Exit the process with [`BROKEN_PIPE_EXIT_STATUS`] once a target turns out to be a closed pipe, instead of
+    /// silently discarding the rest of the output. Defaults to false.
+    pub exit_on_broken_pipe: bool,
+
+    /// Glyphs and colors used by the human readable formatters. Defaults to [`Theme::detect`], which falls back to
+    /// [`Theme::ascii`] on terminals that can't render Unicode.
+    pub theme: Theme,
+
+    /// Whether colors are printed. Defaults to [`ColorMode::Auto`].
+    ///
+    /// Decided for every formatter on its own, so a [`Tee`] can print colors to the terminal while writing a plain
+    /// log file next to it.
+    pub color: ColorMode,
+
+    /// How the Json and Yaml formatters lay out their records. Defaults to [`JsonMode::Lines`].
+    ///
+    /// [`JsonMode::Array`] and [`JsonMode::Document`] hold on to the records meant for the output target and write
+    /// them as one JSON document when the formatter is [finished](Formatter::finish) or dropped, so the output is
+    /// valid JSON even when the program bails out early with an error or a panic. Records routed to the diagnostic
+    /// target are still written as they happen, as are records printed after the document was written and questions,
+    /// which are written to the diagnostic target since the program waits for their answer. Exiting
+    /// with [`std::process::exit`] skips the document, and so does never finishing the
+    /// [global formatter](set_global_formatter), which is never dropped.
+    pub json_mode: JsonMode,
+
+    /// Pretty-print Json records with indentation instead of writing them on a single line. Defaults to false.
+    pub json_pretty: bool,
+
+    /// Timestamps printed in front of each line by Plain, Tree and Spinner. Defaults to [`Timestamps::Off`].
+    pub timestamps: Timestamps,
 }
 
 impl Options {
     /// Sets the debug mode. Debug controls if debug lines are printed are not.
+    ///
+    /// This is shorthand for [`with_verbosity`](Self::with_verbosity) with [`Verbosity::Debug`] when true and
+    /// [`Verbosity::Info`] when false.
     pub fn with_debug(self, debug: bool) -> Self {
-        Self { debug, ..self }
+        let verbosity = if debug {
+            Verbosity::Debug
+        } else {
+            Verbosity::Info
+        };
+
+        Self { verbosity, ..self }
+    }
+
+    /// Sets which messages are printed. See [`Verbosity::from_flags`] to derive it from `-q`/`-v` flags.
+    pub fn with_verbosity(self, verbosity: Verbosity) -> Self {
+        Self { verbosity, ..self }
     }
 
     /// Sets the max line length which controls the character length for lines (also counting indentation).
@@ -263,13 +878,84 @@ impl Options {
     /// since it doesn't makes sense outside a tty context.
     pub fn with_custom_output_target<W: Write + Send + 'static>(self, target: W) -> Self {
         Self {
-            output_target: OutputTarget {
-                kind: OutputTargetKind::Custom,
-                target: Arc::new(Mutex::new(std::io::LineWriter::new(target))),
-            },
+            output_target: OutputTarget::custom(target),
+            ..self
+        }
+    }
+
+    /// Sets the diagnostic target, which receives any messages routed to [`Destination::Diagnostic`]. Defaults to
+    /// stderr.
+    pub fn with_custom_diagnostic_target<W: Write + Send + 'static>(self, target: W) -> Self {
+        Self {
+            diagnostic_target: OutputTarget::custom(target),
+            ..self
+        }
+    }
+
+    /// Sets which target each kind of message is written to. Use [`Routing::diagnostics`] to keep errors and
+    /// warnings out of piped output.
+    pub fn with_routing(self, routing: Routing) -> Self {
+        Self { routing, ..self }
+    }
+
+    /// Sets what the infallible formatter methods do when writing to a target fails. The `try_` methods on
+    /// [`Formatter`] always return the error regardless of this setting.
+    pub fn with_write_error_policy(self, write_error_policy: WriteErrorPolicy) -> Self {
+        Self {
+            write_error_policy,
+            ..self
+        }
+    }
+
+    /// Sets whether the process should exit once output is written to a closed pipe (for example when piped into
+    /// `head`). By default polyfmt stops writing to the pipe and carries on silently; with this set it exits quietly
+    /// with [`BROKEN_PIPE_EXIT_STATUS`], like most command line tools do.
+    pub fn with_exit_on_broken_pipe(self, exit_on_broken_pipe: bool) -> Self {
+        Self {
+            exit_on_broken_pipe,
+            ..self
+        }
+    }
+
+    /// Sets the theme, which controls the glyphs and colors used by the human readable formatters.
+    pub fn with_theme(self, theme: Theme) -> Self {
+        Self { theme, ..self }
+    }
+
+    /// Sets whether colors are printed.
+    pub fn with_color(self, color: ColorMode) -> Self {
+        Self { color, ..self }
+    }
+
+    /// Sets how the Json and Yaml formatters lay out their records.
+    pub fn with_json_mode(self, json_mode: JsonMode) -> Self {
+        Self { json_mode, ..self }
+    }
+
+    /// Sets whether the Json formatter pretty-prints its records.
+    pub fn with_json_pretty(self, json_pretty: bool) -> Self {
+        Self {
+            json_pretty,
             ..self
         }
     }
+
+    /// Sets the timestamps printed in front of each line.
+    pub fn with_timestamps(self, timestamps: Timestamps) -> Self {
+        Self { timestamps, ..self }
+    }
+
+    /// Forces ASCII-only glyphs on or off instead of relying on [`Theme::detect`]. Replaces the theme with
+    /// [`Theme::ascii`] or [`Theme::default`] respectively.
+    pub fn with_ascii(self, ascii: bool) -> Self {
+        let theme = if ascii {
+            Theme::ascii()
+        } else {
+            Theme::default()
+        };
+
+        Self { theme, ..self }
+    }
 }
 
 impl Default for Options {
@@ -280,15 +966,19 @@ impl Default for Options {
         }
 
         Self {
-            debug: Default::default(),
+            verbosity: Verbosity::default(),
             max_line_length,
             padding: 0,
-            output_target: OutputTarget {
-                kind: OutputTargetKind::Stdout,
-                // We default to writing to stdout, but we wrap it in a LineWriter so we consistently flush the buffer
-                // on newlines. This makes it so write buffering is more predictable.
-                target: Arc::new(Mutex::new(std::io::LineWriter::new(std::io::stdout()))),
-            },
+            output_target: OutputTarget::stdout(),
+            diagnostic_target: OutputTarget::stderr(),
+            routing: Routing::default(),
+            write_error_policy: WriteErrorPolicy::default(),
+            exit_on_broken_pipe: false,
+            theme: Theme::detect(),
+            color: ColorMode::default(),
+            json_mode: JsonMode::default(),
+            json_pretty: false,
+            timestamps: Timestamps::default(),
         }
     }
 }
@@ -307,31 +997,158 @@ impl<T: erased_serde::Serialize + Display> Displayable for T {
     }
 }
 
-/// The core library trait.
-pub trait Formatter: Send + Sync {
-    /// Will attempt to intelligently print objects passed to it.
-    ///
-    /// Note: For the spinner format this will add a new persistent message to
-    /// the spinner but not print a brand new line.
-    fn print(&mut self, msg: &dyn Displayable);
+/// A key/value pair attached to a message with [`Formatter::with_fields`].
+///
+/// Values are rendered eagerly since the borrowed value is gone by the time the formatter prints the message.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Field {
+    pub key: String,
 
-    /// Prints the message with same functionality as [`print`](Self::print) but adds a
-    /// newline to the end.
-    fn println(&mut self, msg: &dyn Displayable);
+    /// The value as displayed by human readable formatters.
+    pub text: String,
 
-    /// Prints the message noting it as an error to the user.
-    fn error(&mut self, msg: &dyn Displayable);
+    /// The value as serialized by machine readable formatters.
+    pub value: serde_json::Value,
+}
 
-    /// Prints the message noting it as an error to the user.
-    fn success(&mut self, msg: &dyn Displayable);
+impl Field {
+    pub fn new(key: &str, value: &dyn Displayable) -> Self {
+        Self {
+            key: key.to_string(),
+            text: value.to_string(),
+            value: serde_json::to_value(value.as_serialize())
+                .unwrap_or_else(|_| serde_json::Value::String(value.to_string())),
+        }
+    }
+}
+
+/// Borrows an already rendered [`Field`] value so it can be handed back to [`Formatter::with_fields`] as a
+/// [`Displayable`].
+pub(crate) struct RenderedValue<'a> {
+    text: &'a str,
+    value: &'a serde_json::Value,
+}
+
+impl Display for RenderedValue<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.text)
+    }
+}
+
+impl Serialize for RenderedValue<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serializer)
+    }
+}
+
+/// Attaches owned fields to the next message printed by `formatter`.
+pub(crate) fn with_owned_fields<'a>(
+    formatter: &'a mut dyn Formatter,
+    fields: &[Field],
+) -> &'a mut dyn Formatter {
+    with_borrowed_pairs(fields, |pairs| formatter.with_fields(pairs))
+}
+
+/// Attaches owned metadata to the next message printed by `formatter`.
+pub(crate) fn with_owned_metadata<'a>(
+    formatter: &'a mut dyn Formatter,
+    fields: &[Field],
+) -> &'a mut dyn Formatter {
+    with_borrowed_pairs(fields, |pairs| formatter.with_metadata(pairs))
+}
+
+/// Hands owned fields to `f` as the key/value pairs taken by [`Formatter::with_fields`].
+fn with_borrowed_pairs<T>(fields: &[Field], f: impl FnOnce(&[(&str, &dyn Displayable)]) -> T) -> T {
+    let values: Vec<RenderedValue> = fields
+        .iter()
+        .map(|field| RenderedValue {
+            text: &field.text,
+            value: &field.value,
+        })
+        .collect();
+    let pairs: Vec<(&str, &dyn Displayable)> = fields
+        .iter()
+        .zip(&values)
+        .map(|(field, value)| (field.key.as_str(), value as &dyn Displayable))
+        .collect();
+
+    f(&pairs)
+}
+
+/// Renders fields as a dimmed ` key=value key=value` suffix. Values containing whitespace, quotes or equals signs
+/// are quoted so the pairs stay unambiguous. Returns an empty string when there are no fields.
+fn format_fields(fields: &[Field]) -> String {
+    if fields.is_empty() {
+        return String::new();
+    }
+
+    let pairs: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            let needs_quotes = field.text.is_empty()
+                || field
+                    .text
+                    .chars()
+                    .any(|c| c.is_whitespace() || c == '"' || c == '=');
+
+            if needs_quotes {
+                format!("{}={:?}", field.key, field.text)
+            } else {
+                format!("{}={}", field.key, field.text)
+            }
+        })
+        .collect();
+
+    format!(" {}", ansi::dimmed(&pairs.join(" ")))
+}
+
+/// Wraps a message followed by its rendered fields, so that lines with fields still fit within the max line length.
+fn format_text_with_fields(
+    msg: &str,
+    fields: &[Field],
+    indentation_level: u16,
+    max_line_length: usize,
+) -> Vec<String> {
+    format_text_by_length(
+        &format!("{msg}{}", format_fields(fields)),
+        indentation_level,
+        max_line_length,
+    )
+}
+
+/// The core library trait.
+pub trait Formatter: Send + Sync + AsFormatter {
+    /// Will attempt to intelligently print objects passed to it.
+    ///
+    /// Note: For the spinner format this will add a new persistent message to
+    /// the spinner but not print a brand new line.
+    fn print(&mut self, msg: &dyn Displayable);
+
+    /// Prints the message with same functionality as [`print`](Self::print) but adds a
+    /// newline to the end.
+    fn println(&mut self, msg: &dyn Displayable);
+
+    /// Prints the message noting it as an error to the user.
+    fn error(&mut self, msg: &dyn Displayable);
+
+    /// Prints the message noting it as an error to the user.
+    fn success(&mut self, msg: &dyn Displayable);
 
     /// Prints the message noting it as a warning to the user.
     fn warning(&mut self, msg: &dyn Displayable);
 
-    /// Prints a message only if debug is turned on in the formatter options.
+    /// Prints a message only if the verbosity is [`Verbosity::Debug`] or higher.
     fn debug(&mut self, msg: &dyn Displayable);
 
-    /// Increases the indentation of output.
+    /// Prints a message only if the verbosity is [`Verbosity::Trace`].
+    ///
+    /// Formatters which don't tell trace lines apart print them like [`debug`](Self::debug) lines.
+    fn trace(&mut self, msg: &dyn Displayable) {
+        self.debug(msg);
+    }
+
+    /// Increases the indentation of output. [`Json`](Format::Json) records the nesting as `depth` and `groups` keys
+    /// instead.
     fn indent(&mut self) -> Box<dyn IndentGuard>;
 
     /// Decreases the indentation of output.
@@ -340,6 +1157,46 @@ pub trait Formatter: Send + Sync {
     /// Prints a spacer where the type of spacer is determined by the [`Formatter`]
     fn spacer(&mut self);
 
+    /// Prints rows of data as a table under the given headers.
+    ///
+    /// Human readable formatters align the columns and shrink them to fit within the max line length,
+    /// truncating cells that don't fit. [`Json`](Format::Json) emits the rows as an array of objects keyed by header.
+    ///
+    /// Formatters which don't draw tables themselves print the aligned columns line by line through
+    /// [`println`](Self::println).
+    fn table(&mut self, headers: &[&str], rows: &[Vec<String>]) {
+        let options = Options::default();
+        let (header, body) = format_table_columns(
+            headers,
+            rows,
+            options.max_line_length,
+            &options.theme.ellipsis,
+        );
+
+        self.println(&header);
+        for line in body {
+            self.println(&line);
+        }
+    }
+
+    /// Starts a determinate progress indicator that counts up to `total`.
+    ///
+    /// The [`Spinner`](Format::Spinner) format draws a progress bar with an ETA, [`Plain`](Format::Plain) and
+    /// [`Tree`](Format::Tree) print a line every ten percent and [`Json`](Format::Json) emits throttled
+    /// `progress` records. Formatters which don't show progress return a handle which does nothing.
+    fn progress(&mut self, _total: u64, _msg: &dyn Displayable) -> Box<dyn ProgressHandle> {
+        Box::new(progress::NoopProgress)
+    }
+
+    /// Starts a named task which reports its status independently of other tasks.
+    ///
+    /// The [`Spinner`](Format::Spinner) format gives every task its own spinner line, [`Plain`](Format::Plain) and
+    /// [`Tree`](Format::Tree) print lines prefixed with the task name and [`Json`](Format::Json) emits `task`
+    /// records tagged with the task name. Formatters which don't show tasks return a handle which does nothing.
+    fn task(&mut self, _name: &dyn Displayable) -> Box<dyn TaskHandle> {
+        Box::new(task::NoopTask)
+    }
+
     /// Temporarily pauses dynamic or animated output.
     ///
     /// This is primarily used by formatters that render animated elements such as
@@ -373,7 +1230,112 @@ pub trait Formatter: Send + Sync {
     /// formats mentioned
     fn only(&mut self, types: Vec<Format>) -> &mut dyn Formatter;
 
+    /// Attaches structured key/value fields to the next message printed.
+    ///
+    /// Human readable formatters render the fields as dimmed `key=value` pairs after the message, while
+    /// [`Json`](Format::Json) adds them to the record as a `fields` object. Like [`only`](Self::only) this
+    /// only applies to the very next call. Formatters which don't render fields ignore them.
+    fn with_fields(&mut self, _fields: &[(&str, &dyn Displayable)]) -> &mut dyn Formatter {
+        self.as_formatter()
+    }
+
+    /// Attaches fields describing where the next message came from, such as the target of a log record or the
+    /// fields of enclosing tracing spans.
+    ///
+    /// Machine readable formatters add them to the record like fields from [`with_fields`](Self::with_fields),
+    /// which take precedence on conflicting keys. Human readable formatters drop them to keep lines short. Like
+    /// [`with_fields`](Self::with_fields) this only applies to the very next call.
+    fn with_metadata(&mut self, _fields: &[(&str, &dyn Displayable)]) -> &mut dyn Formatter {
+        self.as_formatter()
+    }
+
+    /// Returns the concrete format this formatter renders. This is mostly useful after constructing a
+    /// formatter with [`Format::Auto`] to find out what it resolved to. Formatters which don't render one of the
+    /// built-in formats return [`Format::Auto`].
+    fn format(&self) -> Format {
+        Format::Auto
+    }
+
+    /// Flushes any buffered output and cleans up anything else the formatter needs to before the program exits.
     fn finish(&self);
+
+    /// Fallible version of [`print`](Self::print).
+    fn try_print(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        self.print(msg);
+        Ok(())
+    }
+
+    /// Fallible version of [`println`](Self::println).
+    fn try_println(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        self.println(msg);
+        Ok(())
+    }
+
+    /// Fallible version of [`error`](Self::error).
+    fn try_error(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        self.error(msg);
+        Ok(())
+    }
+
+    /// Fallible version of [`success`](Self::success).
+    fn try_success(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        self.success(msg);
+        Ok(())
+    }
+
+    /// Fallible version of [`warning`](Self::warning).
+    fn try_warning(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        self.warning(msg);
+        Ok(())
+    }
+
+    /// Fallible version of [`debug`](Self::debug).
+    fn try_debug(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        self.debug(msg);
+        Ok(())
+    }
+
+    /// Fallible version of [`trace`](Self::trace).
+    fn try_trace(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        self.trace(msg);
+        Ok(())
+    }
+
+    /// Fallible version of [`spacer`](Self::spacer).
+    fn try_spacer(&mut self) -> io::Result<()> {
+        self.spacer();
+        Ok(())
+    }
+
+    /// Fallible version of [`table`](Self::table).
+    fn try_table(&mut self, headers: &[&str], rows: &[Vec<String>]) -> io::Result<()> {
+        self.table(headers, rows);
+        Ok(())
+    }
+
+    /// Fallible version of [`question`](Self::question). Errors reading the answer are returned as well.
+    fn try_question(&mut self, msg: &dyn Displayable) -> io::Result<String> {
+        Ok(self.question(msg))
+    }
+
+    /// Fallible version of [`finish`](Self::finish). Returns the error recorded by
+    /// [`WriteErrorPolicy::Record`] if there is one, otherwise any error hit while flushing.
+    fn try_finish(&self) -> io::Result<()> {
+        self.finish();
+        Ok(())
+    }
+}
+
+/// Turns a formatter into a [`Formatter`] trait object, so that default methods of [`Formatter`] can return `self`.
+/// Implemented for every formatter.
+pub trait AsFormatter {
+    fn as_formatter(&mut self) -> &mut dyn Formatter;
+}
+
+impl<T: Formatter> AsFormatter for T {
+    fn as_formatter(&mut self) -> &mut dyn Formatter {
+        self
+    }
 }
 
 /// Instantiates a Global formatter for easy use. This formatter can be altered by the library
@@ -384,8 +1346,14 @@ static GLOBAL_FORMATTER: Lazy<Mutex<Box<dyn Formatter>>> = Lazy::new(|| {
 });
 
 /// Set the global formatter to a custom formatter.
+///
+/// The global formatter lives in a static and is never dropped, so call [`finish!`](crate::finish) before the
+/// program exits. Otherwise buffered output, like the document of [`JsonMode::Array`] and [`JsonMode::Document`],
+/// is never written. The formatter being replaced is dropped, which finishes it.
 pub fn set_global_formatter(formatter: Box<dyn Formatter>) {
-    *GLOBAL_FORMATTER.lock().unwrap() = formatter;
+    *GLOBAL_FORMATTER
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = formatter;
 }
 
 /// Return the current global formatter. Mainly used for macros, should be unneeded for scoped formatters.
@@ -405,8 +1373,17 @@ pub fn get_global_formatter() -> &'static Mutex<Box<dyn Formatter>> {
 /// // You can also specify that certain lines be printed only when certain formatters are in effect.
 /// fmt.only(vec![Format::Plain]).error(&"test");
 /// ```
-pub fn new(format: Format, options: Options) -> Box<dyn Formatter> {
-    match format {
+pub fn new(format: Format, mut options: Options) -> Box<dyn Formatter> {
+    // Formatters always style their output. Rather than turning colors off for the whole process, a formatter
+    // without colors has the escape sequences stripped from its targets.
+    if !options.color.enabled() {
+        options.theme = options.theme.without_colors();
+        options.output_target.target = ansi::StripColors::wrap(options.output_target.target);
+        options.diagnostic_target.target =
+            ansi::StripColors::wrap(options.diagnostic_target.target);
+    }
+
+    match format.resolve(&options.output_target) {
         Format::Plain => {
             let formatter = plain::Plain::new(options);
             Box::new(formatter)
@@ -427,13 +1404,18 @@ pub fn new(format: Format, options: Options) -> Box<dyn Formatter> {
             Box::new(formatter)
         }
         Format::Json => {
-            let formatter = json::Json::new(options);
+            let formatter = json::Json::new(options, json::Encoding::Json);
+            Box::new(formatter)
+        }
+        Format::Yaml => {
+            let formatter = json::Json::new(options, json::Encoding::Yaml);
             Box::new(formatter)
         }
         Format::Silent => {
             let formatter = silent::Silent {};
             Box::new(formatter)
         }
+        Format::Auto => unreachable!("auto format is always resolved to a concrete format"),
     }
 }
 
@@ -442,35 +1424,50 @@ fn split_on_whitespace_keep_delimiter_grouped(s: &str) -> Vec<String> {
     let mut current_chunk = String::new();
     let mut inside_whitespace = false;
 
-    for c in s.chars() {
-        if c.is_whitespace() {
-            if inside_whitespace {
-                // If the current character matches the type of the current whitespace chunk, add it
-                if current_chunk.chars().next().unwrap().is_whitespace()
-                    && c == current_chunk.chars().next().unwrap()
-                {
-                    current_chunk.push(c);
+    for segment in ansi::segments(s) {
+        let text = match segment {
+            ansi::Segment::Text(text) => text,
+            // Escape sequences stick to the word they style, even when they contain whitespace themselves.
+            ansi::Segment::Escape(escape) => {
+                if inside_whitespace {
+                    result.push(std::mem::take(&mut current_chunk));
+                    inside_whitespace = false;
+                }
+                current_chunk += escape;
+                continue;
+            }
+        };
+
+        for c in text.chars() {
+            if c.is_whitespace() {
+                if inside_whitespace {
+                    // If the current character matches the type of the current whitespace chunk, add it
+                    if current_chunk.chars().next().unwrap().is_whitespace()
+                        && c == current_chunk.chars().next().unwrap()
+                    {
+                        current_chunk.push(c);
+                    } else {
+                        // Different type of whitespace, push the old one, start a new one
+                        result.push(current_chunk);
+                        current_chunk = c.to_string();
+                    }
                 } else {
-                    // Different type of whitespace, push the old one, start a new one
-                    result.push(current_chunk);
+                    // Transitioning from text to whitespace
+                    if !current_chunk.is_empty() {
+                        result.push(current_chunk);
+                    }
                     current_chunk = c.to_string();
+                    inside_whitespace = true;
                 }
-            } else {
-                // Transitioning from text to whitespace
-                if !current_chunk.is_empty() {
-                    result.push(current_chunk);
-                }
+            } else if inside_whitespace {
+                // Transitioning from whitespace to text
+                result.push(current_chunk);
                 current_chunk = c.to_string();
-                inside_whitespace = true;
+                inside_whitespace = false;
+            } else {
+                // Continuing with text
+                current_chunk.push(c);
             }
-        } else if inside_whitespace {
-            // Transitioning from whitespace to text
-            result.push(current_chunk);
-            current_chunk = c.to_string();
-            inside_whitespace = false;
-        } else {
-            // Continuing with text
-            current_chunk.push(c);
         }
     }
 
@@ -482,8 +1479,87 @@ fn split_on_whitespace_keep_delimiter_grouped(s: &str) -> Vec<String> {
     result
 }
 
+/// Number of terminal columns the text takes up. East Asian wide characters and most emoji count as two columns,
+/// combining marks, escape sequences and other zero width characters as none.
+fn display_width(text: &str) -> usize {
+    ansi::segments(text)
+        .into_iter()
+        .map(|segment| match segment {
+            ansi::Segment::Escape(_) => 0,
+            ansi::Segment::Text(text) => text.width(),
+        })
+        .sum()
+}
+
+/// Collects wrapped lines while keeping colors and hyperlinks from bleeding across them. Styles which are still
+/// active at the end of a line are closed there and opened again at the start of the next one.
+#[derive(Default)]
+struct LineWrapper {
+    lines: Vec<String>,
+    current_line: String,
+    current_width: usize,
+    reopened_len: usize,
+    styles: ansi::ActiveStyles,
+}
+
+impl LineWrapper {
+    fn push(&mut self, text: &str) {
+        for segment in ansi::segments(text) {
+            match segment {
+                ansi::Segment::Escape(escape) => {
+                    self.styles.observe(escape);
+                    self.current_line += escape;
+                }
+                ansi::Segment::Text(text) => {
+                    self.current_line += text;
+                    self.current_width += text.width();
+                }
+            }
+        }
+    }
+
+    /// Like push, but breaks the text between grapheme clusters wherever the line runs out.
+    fn push_breaking(&mut self, text: &str, max_line_width: usize) {
+        for segment in ansi::segments(text) {
+            let text = match segment {
+                ansi::Segment::Escape(escape) => {
+                    self.push(escape);
+                    continue;
+                }
+                ansi::Segment::Text(text) => text,
+            };
+
+            for grapheme in text.graphemes(true) {
+                if self.current_width + grapheme.width() > max_line_width && self.current_width > 0
+                {
+                    self.end_line();
+                }
+                self.push(grapheme);
+            }
+        }
+    }
+
+    fn end_line(&mut self) {
+        self.current_line += &self.styles.close();
+        self.lines.push(std::mem::take(&mut self.current_line));
+        self.current_line = self.styles.reopen();
+        self.reopened_len = self.current_line.len();
+        self.current_width = 0;
+    }
+
+    fn finish(mut self) -> Vec<String> {
+        if self.current_line.len() > self.reopened_len {
+            self.lines.push(self.current_line);
+        }
+        self.lines
+    }
+}
+
 /// Convenience function to chunk lines of text based on the max line length,
 /// respecting original whitespace, newlines, and avoiding splitting words across lines.
+///
+/// Lengths are measured in terminal columns rather than bytes, and escape sequences (like colors) don't count
+/// towards them. Words which are wider than a whole line are broken between grapheme clusters instead of overflowing.
 fn format_text_by_length(
     msg: &dyn Displayable,
     indentation_level: u16,
@@ -501,8 +1577,7 @@ fn format_text_by_length(
     }
 
     let max_line_width = max_line_length - indentation_level;
-    let mut lines = Vec::new();
-    let mut current_line = String::new();
+    let mut wrapper = LineWrapper::default();
 
     for word in split_on_whitespace_keep_delimiter_grouped(&msg) {
         // If we encounter a new line character that is a sign to immediately
@@ -511,46 +1586,43 @@ fn format_text_by_length(
         // which show up as "\n\n". In this case we should add that amount of newlines.
         if word.starts_with('\n') {
             for _ in word.chars() {
-                lines.push(current_line.clone());
-                current_line = String::new();
+                wrapper.end_line();
             }
             continue;
         }
 
         // If the word is just a space character we don't want to preserve it when
         // starting a new line, so we just skip it.
-        if current_line.is_empty() && word.len() == 1 && word.starts_with(' ') {
+        if wrapper.current_width == 0 && word == " " {
             continue;
         }
 
         // If the word we're currently processing doesn't make the line
         // longer than the limit we simply add it to the current_line.
-        if (current_line.len() + word.len()) <= max_line_width {
-            current_line += &word;
+        if wrapper.current_width + display_width(&word) <= max_line_width {
+            wrapper.push(&word);
             continue;
         }
 
         // If the word we're processing DOES make the line longer then the
         // limit we first add the current line to the list of lines and then
         // we create a new line and add it to that line.
-        lines.push(current_line.clone());
-        current_line = String::new();
+        if wrapper.current_width > 0 {
+            wrapper.end_line();
+        }
 
         // If the word is just a space character we don't want to preserve it when
         // starting a new line, so we just skip it.
-        if word.len() == 1 && word.starts_with(' ') {
+        if word == " " {
             continue;
         }
 
-        current_line += &word;
+        // A word that doesn't even fit on a line of its own gets broken wherever the line runs out.
+        wrapper.push_breaking(&word, max_line_width);
     }
 
     // Make sure that the last line is added.
-    if !current_line.is_empty() {
-        lines.push(current_line.clone());
-    }
-
-    lines
+    wrapper.finish()
 }
 
 /// Enables the spinner to automatically clean itself up, when dropped.
@@ -588,6 +1660,185 @@ impl Spinner {
     }
 }
 
+/// Calculates how wide each column of a table should be so that the table fits within `max_width`.
+///
+/// `overhead` is the amount of characters the table spends on things other than cell content (column separators,
+/// borders, etc). Columns start at the width of their widest cell and the widest column is shrunk one character
+/// at a time until the table fits or every column is down to a single character.
+fn table_column_widths(
+    headers: &[&str],
+    rows: &[Vec<String>],
+    max_width: usize,
+    overhead: usize,
+) -> Vec<usize> {
+    let mut widths: Vec<usize> = headers.iter().map(|h| display_width(h)).collect();
+
+    for row in rows {
+        for (index, cell) in row.iter().enumerate().take(widths.len()) {
+            widths[index] = widths[index].max(display_width(cell));
+        }
+    }
+
+    let available = max_width.saturating_sub(overhead);
+
+    while widths.iter().sum::<usize>() > available {
+        let (widest, width) = widths
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, width)| **width)
+            .map(|(index, width)| (index, *width))
+            .unwrap_or((0, 0));
+
+        if width <= 1 {
+            break;
+        }
+
+        widths[widest] -= 1;
+    }
+
+    widths
+}
+
+/// Fits a single table cell into exactly `width` columns, padding it with spaces or truncating it with the given
+/// ellipsis. Newlines are flattened since a cell always occupies a single line.
+fn fit_table_cell(cell: &str, width: usize, ellipsis: &str) -> String {
+    let cell = cell.replace(['\n', '\r'], " ");
+    let length = display_width(&cell);
+
+    if length <= width {
+        return format!("{cell}{}", " ".repeat(width - length));
+    }
+
+    if width == 0 {
+        return String::new();
+    }
+
+    // Cells narrower than the ellipsis show as much of the ellipsis as fits.
+    let ellipsis_width = display_width(ellipsis);
+    if ellipsis_width > width {
+        return fit_table_cell(ellipsis, width, "");
+    }
+
+    // Wide characters can leave a single column free in front of the ellipsis, which gets padded instead.
+    let mut truncated = String::new();
+    let mut truncated_width = 0;
+    let mut styles = ansi::ActiveStyles::default();
+    'segments: for segment in ansi::segments(&cell) {
+        let text = match segment {
+            ansi::Segment::Escape(escape) => {
+                styles.observe(escape);
+                truncated += escape;
+                continue;
+            }
+            ansi::Segment::Text(text) => text,
+        };
+
+        for grapheme in text.graphemes(true) {
+            let grapheme_width = grapheme.width();
+            if truncated_width + grapheme_width > width - ellipsis_width {
+                break 'segments;
+            }
+            truncated += grapheme;
+            truncated_width += grapheme_width;
+        }
+    }
+
+    format!(
+        "{truncated}{ellipsis}{}{}",
+        styles.close(),
+        " ".repeat(width - ellipsis_width - truncated_width)
+    )
+}
+
+/// Renders a table as aligned, space separated columns. The header row is returned separately so that
+/// formatters can style it.
+fn format_table_columns(
+    headers: &[&str],
+    rows: &[Vec<String>],
+    max_width: usize,
+    ellipsis: &str,
+) -> (String, Vec<String>) {
+    let separator = "  ";
+    let overhead = separator.len() * headers.len().saturating_sub(1);
+    let widths = table_column_widths(headers, rows, max_width, overhead);
+
+    let render_row = |cells: Vec<&str>| -> String {
+        let line = widths
+            .iter()
+            .enumerate()
+            .map(|(index, width)| {
+                fit_table_cell(cells.get(index).copied().unwrap_or(""), *width, ellipsis)
+            })
+            .collect::<Vec<_>>()
+            .join(separator);
+        line.trim_end().to_string()
+    };
+
+    let header = render_row(headers.to_vec());
+    let body = rows
+        .iter()
+        .map(|row| render_row(row.iter().map(String::as_str).collect()))
+        .collect();
+
+    (header, body)
+}
+
+/// Renders a table with box drawing characters around every cell.
+fn format_table_boxed(
+    headers: &[&str],
+    rows: &[Vec<String>],
+    max_width: usize,
+    borders: &Borders,
+    ellipsis: &str,
+) -> Vec<String> {
+    // Every column costs a leading vertical border and space and a trailing space, plus the final closing border.
+    let overhead = headers.len() * 3 + 1;
+    let widths = table_column_widths(headers, rows, max_width, overhead);
+
+    let border = |left: char, middle: char, right: char| -> String {
+        let segments: Vec<String> = widths
+            .iter()
+            .map(|width| borders.horizontal.to_string().repeat(width + 2))
+            .collect();
+        format!("{left}{}{right}", segments.join(&middle.to_string()))
+    };
+
+    let render_row = |cells: Vec<&str>| -> String {
+        let cells: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(index, width)| {
+                fit_table_cell(cells.get(index).copied().unwrap_or(""), *width, ellipsis)
+            })
+            .collect();
+        let vertical = borders.vertical;
+        format!(
+            "{vertical} {} {vertical}",
+            cells.join(&format!(" {vertical} "))
+        )
+    };
+
+    let mut lines = vec![
+        border(borders.top_left, borders.top_middle, borders.top_right),
+        render_row(headers.to_vec()),
+    ];
+    lines.push(border(
+        borders.middle_left,
+        borders.middle,
+        borders.middle_right,
+    ));
+    for row in rows {
+        lines.push(render_row(row.iter().map(String::as_str).collect()));
+    }
+    lines.push(border(
+        borders.bottom_left,
+        borders.bottom_middle,
+        borders.bottom_right,
+    ));
+
+    lines
+}
+
 /// Drains `allowed_formats` and returns true if the current format is allowed.
 /// Leaves `allowed_formats` empty regardless.
 fn take_and_check_allowed(current: Format, allowed_formats: &mut HashSet<Format>) -> bool {
@@ -602,24 +1853,21 @@ fn take_and_check_allowed(current: Format, allowed_formats: &mut HashSet<Format>
 
 #[cfg(test)]
 mod tests {
-    use crate::{Format, format_text_by_length, take_and_check_allowed};
-    use rstest::rstest;
-    use std::{
-        collections::HashSet,
-        io::{self, Write},
-        sync::{Arc, Mutex},
+    use crate::{
+        Format, Formatter, fit_table_cell, format_table_boxed, format_table_columns,
+        format_text_by_length, resolve_auto_format, take_and_check_allowed,
+        testing::{Capture, EventKind, SharedBuffer},
     };
+    use rstest::rstest;
+    use std::collections::HashSet;
+    use std::io::{self, Write};
 
-    #[derive(Clone, Default)]
-    struct SharedBuffer {
-        inner: Arc<Mutex<Vec<u8>>>,
-    }
+    /// Writer which fails every write, like a full disk would.
+    struct FailingWriter;
 
-    impl Write for SharedBuffer {
-        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-            let mut inner = self.inner.lock().unwrap();
-            inner.extend_from_slice(buf);
-            Ok(buf.len())
+    impl Write for FailingWriter {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk full"))
         }
 
         fn flush(&mut self) -> io::Result<()> {
@@ -627,13 +1875,6 @@ mod tests {
         }
     }
 
-    impl SharedBuffer {
-        fn into_string(self) -> String {
-            let inner = self.inner.lock().unwrap();
-            String::from_utf8_lossy(&inner).to_string()
-        }
-    }
-
     #[rstest]
     #[case::group_similar_whitespace("Hello, there   beautiful", vec!["Hello,", " ", "there", "   ", "beautiful"])]
     #[case::differ_between_whitespace_types("The quick\nbrown fox", vec!["The", " ", "quick", "\n", "brown", " ", "fox"])]
@@ -643,6 +1884,8 @@ mod tests {
     #[case::tabs_and_spaces("Mixed   \t tabs and spaces", vec!["Mixed", "   ", "\t", " ", "tabs", " ", "and", " ", "spaces"])]
     #[case::empty_string("", vec![])] // Empty string
     #[case::trailing_newlines("Sentence then trailingnewlines\n\n", vec!["Sentence", " ", "then", " ", "trailingnewlines", "\n\n"])] // trailing newlines
+    #[case::escapes_stick_to_words("\x1b[31mred text\x1b[0m", vec!["\x1b[31mred", " ", "text\x1b[0m"])]
+    #[case::escapes_containing_spaces("\x1b]0;a title\x07 word", vec!["\x1b]0;a title\x07", " ", "word"])]
     fn test_split_on_whitespace_keep_delimiter_grouped(
         #[case] input: &str,
         #[case] expected: Vec<&str>,
@@ -663,6 +1906,109 @@ mod tests {
         assert_eq!(format_text_by_length(&input, 0, 40), expected)
     }
 
+    #[rstest]
+    #[case::wide_characters("日本語 テキスト 折り返し", 10, vec!["日本語 ", "テキスト ", "折り返し"])]
+    #[case::accents_count_as_one_column("café naïve résumé", 17, vec!["café naïve résumé"])]
+    #[case::emoji_count_as_two_columns("👍👍👍 ok go", 9, vec!["👍👍👍 ok", "go"])]
+    #[case::hard_break_long_word("abcdefghijklmnop", 5, vec!["abcde", "fghij", "klmno", "p"])]
+    #[case::hard_break_after_text("see https://example.com/a/long/path", 10, vec!["see ", "https://ex", "ample.com/", "a/long/pat", "h"])]
+    #[case::never_split_wide_characters("日本語", 5, vec!["日本", "語"])]
+    #[case::never_split_graphemes("e\u{301}e\u{301}e\u{301}", 2, vec!["e\u{301}e\u{301}", "e\u{301}"])]
+    fn format_text_length_uses_display_width(
+        #[case] input: &str,
+        #[case] max_line_length: usize,
+        #[case] expected: Vec<&str>,
+    ) {
+        assert_eq!(format_text_by_length(&input, 0, max_line_length), expected)
+    }
+
+    #[rstest]
+    #[case::colors_are_zero_width("\x1b[31mred\x1b[0m words", 9, vec!["\x1b[31mred\x1b[0m words"])]
+    #[case::colors_reopen_on_continuation_lines("\x1b[31mred words wrap here\x1b[0m", 10, vec!["\x1b[31mred words \x1b[0m", "\x1b[31mwrap here\x1b[0m"])]
+    #[case::colors_reopen_after_newlines("\x1b[1;31mbold\nred\x1b[0m", 40, vec!["\x1b[1;31mbold\x1b[0m", "\x1b[1;31mred\x1b[0m"])]
+    #[case::nested_colors_reopen_in_order("\x1b[1mbold \x1b[32mgreen\x1b[0m", 5, vec!["\x1b[1mbold \x1b[0m", "\x1b[1m\x1b[32mgreen\x1b[0m"])]
+    #[case::reset_is_not_reopened("\x1b[31mred\x1b[0m and plain", 9, vec!["\x1b[31mred\x1b[0m and ", "plain"])]
+    #[case::hard_break_reopens_colors("\x1b[1mabcdefgh\x1b[0m", 5, vec!["\x1b[1mabcde\x1b[0m", "\x1b[1mfgh\x1b[0m"])]
+    #[case::hyperlinks_reopen("\x1b]8;;https://example.com\x1b\\click this link\x1b]8;;\x1b\\", 10, vec!["\x1b]8;;https://example.com\x1b\\click this\x1b]8;;\x1b\\", "\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\"])]
+    fn format_text_length_skips_escape_sequences(
+        #[case] input: &str,
+        #[case] max_line_length: usize,
+        #[case] expected: Vec<&str>,
+    ) {
+        assert_eq!(format_text_by_length(&input, 0, max_line_length), expected)
+    }
+
+    #[test]
+    fn table_cells_skip_escape_sequences() {
+        assert_eq!(
+            fit_table_cell("\x1b[32mok\x1b[0m", 4, "…"),
+            "\x1b[32mok\x1b[0m  "
+        );
+        assert_eq!(
+            fit_table_cell("\x1b[32mrunning\x1b[0m", 5, "…"),
+            "\x1b[32mrunn…\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn table_columns_align_and_shrink_to_fit() {
+        let headers = ["NAME", "STATUS"];
+        let rows = vec![
+            vec!["api".to_string(), "running".to_string()],
+            vec!["worker-long-name".to_string(), "stopped".to_string()],
+        ];
+
+        let (header, body) = format_table_columns(&headers, &rows, 80, "…");
+        assert_eq!(header, "NAME              STATUS");
+        assert_eq!(
+            body,
+            vec!["api               running", "worker-long-name  stopped"]
+        );
+
+        let (header, body) = format_table_columns(&headers, &rows, 16, "…");
+        assert_eq!(header, "NAME     STATUS");
+        assert_eq!(body, vec!["api      running", "worker…  stopped"]);
+    }
+
+    #[test]
+    fn table_cells_use_the_given_ellipsis() {
+        assert_eq!(fit_table_cell("worker-long-name", 8, "..."), "worke...");
+        assert_eq!(fit_table_cell("worker-long-name", 2, "..."), "..");
+    }
+
+    #[test]
+    fn table_columns_measure_display_width() {
+        let headers = ["NAME", "CITY"];
+        let rows = vec![
+            vec!["東京都".to_string(), "Tokyo".to_string()],
+            vec!["api".to_string(), "x".to_string()],
+        ];
+
+        let (header, body) = format_table_columns(&headers, &rows, 80, "…");
+        assert_eq!(header, "NAME    CITY");
+        assert_eq!(body, vec!["東京都  Tokyo", "api     x"]);
+
+        let (header, body) = format_table_columns(&headers, &rows, 10, "…");
+        assert_eq!(header, "NAME  CITY");
+        assert_eq!(body, vec!["東…   Tok…", "api   x"]);
+    }
+
+    #[test]
+    fn table_boxed_draws_borders() {
+        let rows = vec![vec!["a".to_string(), "1".to_string()]];
+        let lines = format_table_boxed(&["NAME", "N"], &rows, 80, &crate::Borders::UNICODE, "…");
+        assert_eq!(
+            lines,
+            vec![
+                "┌──────┬───┐",
+                "│ NAME │ N │",
+                "├──────┼───┤",
+                "│ a    │ 1 │",
+                "└──────┴───┘",
+            ]
+        );
+    }
+
     #[rstest]
     #[case::allowed(vec![Format::Plain, Format::Json], Format::Plain, true)]
     #[case::not_allowed(vec![Format::Spinner], Format::Json, false)]
@@ -677,6 +2023,37 @@ mod tests {
         assert!(allowed.is_empty());
     }
 
+    #[rstest]
+    #[case::interactive_terminal(true, vec![], Format::Spinner)]
+    #[case::piped(false, vec![], Format::Plain)]
+    #[case::ci(true, vec![("CI", "true")], Format::Json)]
+    #[case::ci_disabled(true, vec![("CI", "false")], Format::Spinner)]
+    #[case::no_color(true, vec![("NO_COLOR", "1")], Format::Plain)]
+    #[case::dumb_terminal(true, vec![("TERM", "dumb")], Format::Plain)]
+    #[case::override_wins(false, vec![("POLYFMT_FORMAT", "tree"), ("CI", "1")], Format::Tree)]
+    #[case::invalid_override_ignored(false, vec![("POLYFMT_FORMAT", "fancy")], Format::Plain)]
+    fn auto_format_resolution(
+        #[case] is_terminal: bool,
+        #[case] env: Vec<(&str, &str)>,
+        #[case] expected: Format,
+    ) {
+        let lookup = |key: &str| {
+            env.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.to_string())
+        };
+        assert_eq!(resolve_auto_format(is_terminal, lookup), expected);
+    }
+
+    #[test]
+    fn auto_format_with_custom_target_is_never_a_spinner() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default().with_custom_output_target(sink);
+        let fmt = crate::new(Format::Auto, opts);
+        assert_ne!(fmt.format(), Format::Spinner);
+        assert_ne!(fmt.format(), Format::Auto);
+    }
+
     #[test]
     fn plain_outputs_and_respects_debug_and_indent() {
         unsafe {
@@ -731,4 +2108,1621 @@ mod tests {
         assert_eq!(success.get("data").unwrap(), "ok");
         assert!(!output.contains("hidden"));
     }
+
+    #[test]
+    fn yaml_outputs_documents_and_respects_filters() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default().with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Yaml, opts);
+
+        fmt.println(&"hello");
+        {
+            let _guard = fmt.indent();
+            fmt.with_fields(&[("region", &"us")]).warning(&"careful");
+        }
+        fmt.only(vec![Format::Json]).println(&"json only");
+        fmt.only(vec![Format::Yaml]).success(&"yaml only");
+        fmt.debug(&"hidden");
+        fmt.table(&["name"], &[vec!["api".to_string()]]);
+        fmt.finish();
+
+        let output = sink.into_string();
+        assert_eq!(output.matches("---\n").count(), 4);
+
+        let records: Vec<serde_json::Value> = serde_yaml::Deserializer::from_str(&output)
+            .map(|document| serde::Deserialize::deserialize(document).unwrap())
+            .collect();
+        let lines: String = records.iter().map(|record| format!("{record}\n")).collect();
+        assert_eq!(
+            strip_metadata(&lines),
+            "{\"data\":\"hello\",\"depth\":0,\"groups\":[],\"label\":\"info\"}\n\
+             {\"data\":\"careful\",\"depth\":1,\"fields\":{\"region\":\"us\"},\"groups\":[1],\"label\":\"warning\"}\n\
+             {\"data\":\"yaml only\",\"depth\":0,\"groups\":[],\"label\":\"success\"}\n\
+             {\"data\":[{\"name\":\"api\"}],\"depth\":0,\"groups\":[],\"label\":\"table\"}\n"
+        );
+        assert!(
+            records
+                .iter()
+                .all(|record| record["schema_version"] == crate::schema::SCHEMA_VERSION)
+        );
+        assert!(records.iter().all(|record| record["ts"].is_string()));
+    }
+
+    #[test]
+    fn plain_progress_prints_a_line_per_step() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default().with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Plain, opts);
+
+        let progress = fmt.progress(4, &"download");
+        for _ in 0..4 {
+            progress.inc(1);
+        }
+        progress.finish();
+        fmt.finish();
+
+        assert_eq!(
+            sink.into_string(),
+            "download 0/4 (0%)\ndownload 1/4 (25%)\ndownload 2/4 (50%)\ndownload 3/4 (75%)\ndownload 4/4 (100%)\n"
+        );
+    }
+
+    #[test]
+    fn json_progress_reports_start_and_finish() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default().with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Json, opts);
+
+        let progress = fmt.progress(120, &"batch");
+        progress.set_position(37);
+        progress.finish();
+        fmt.finish();
+
+        let output = sink.into_string();
+        let records: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        let first = records.first().unwrap();
+        let last = records.last().unwrap();
+        assert_eq!(first["label"], "progress");
+        assert_eq!(first["current"], 0);
+        assert_eq!(first["total"], 120);
+        assert_eq!(last["current"], 120);
+        assert_eq!(last["data"], "batch");
+    }
+
+    #[test]
+    fn json_progress_reports_the_last_position_when_dropped() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default().with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Json, opts);
+
+        let progress = fmt.progress(120, &"batch");
+        progress.set_position(37);
+        progress.set_position(42);
+        drop(progress);
+        fmt.finish();
+
+        let currents: Vec<serde_json::Value> = sink
+            .into_string()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["current"].clone())
+            .collect();
+        assert_eq!(currents, vec![0, 42]);
+    }
+
+    #[test]
+    fn progress_and_task_lines_leave_pending_fields_alone() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default().with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Plain, opts);
+
+        let progress = fmt.progress(10, &"download");
+        let task = fmt.task(&"build");
+        fmt.with_fields(&[("id", &42)]).only(vec![Format::Plain]);
+        progress.set_position(5);
+        task.set_message(&"compiling");
+        fmt.println(&"done");
+        fmt.finish();
+
+        assert_eq!(
+            sink.into_string(),
+            "download 0/10 (0%)\n[build] started\ndownload 5/10 (50%)\n[build] compiling\ndone id=42\n"
+        );
+    }
+
+    #[test]
+    fn plain_tasks_print_prefixed_lines_in_order() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default().with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Plain, opts);
+
+        let api = fmt.task(&"build api");
+        let web = fmt.task(&"build web");
+        api.set_message(&"compiling");
+        web.error(&"failed");
+        api.success(&"done");
+        api.error(&"ignored after finishing");
+        fmt.finish();
+
+        let output = sink.into_string();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "[build api] started");
+        assert_eq!(lines[1], "[build web] started");
+        assert_eq!(lines[2], "[build api] compiling");
+        assert!(lines[3].ends_with("[build web] failed"));
+        assert!(lines[4].ends_with("[build api] done"));
+        assert_eq!(lines.len(), 5);
+    }
+
+    #[test]
+    fn json_tasks_are_tagged_with_their_name() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default().with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Json, opts);
+
+        let task = fmt.task(&"deploy");
+        let handle = std::thread::spawn(move || {
+            task.set_message(&"uploading");
+            task.success(&"deployed");
+        });
+        handle.join().unwrap();
+        fmt.finish();
+
+        let output = sink.into_string();
+        let records: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        let statuses: Vec<&str> = records
+            .iter()
+            .map(|record| record["status"].as_str().unwrap())
+            .collect();
+        assert_eq!(statuses, vec!["started", "running", "success"]);
+        assert!(records.iter().all(|record| record["label"] == "task"));
+        assert!(records.iter().all(|record| record["task"] == "deploy"));
+        assert_eq!(records[2]["data"], "deployed");
+    }
+
+    #[test]
+    fn plain_renders_fields_as_suffix() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default()
+            .with_color(crate::ColorMode::Never)
+            .with_ascii(false)
+            .with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Plain, opts);
+
+        fmt.with_fields(&[("id", &42), ("region", &"us east")])
+            .error(&"failed to fetch");
+        fmt.println(&"no fields here");
+        fmt.finish();
+
+        assert_eq!(
+            sink.into_string(),
+            "x failed to fetch id=42 region=\"us east\"\nno fields here\n"
+        );
+    }
+
+    #[test]
+    fn spinner_writes_lines_to_the_output_target() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default()
+            .with_color(crate::ColorMode::Never)
+            .with_ascii(false)
+            .with_custom_output_target(sink.clone());
+        let mut fmt: Box<dyn Formatter> = Box::new(crate::spinner::Spinner::new(opts));
+
+        fmt.println(&"deploying");
+        fmt.success(&"deployed");
+        fmt.table(&["name"], &[vec!["api".to_string()]]);
+        fmt.finish();
+
+        let output = crate::ansi::strip_colors(&sink.into_string());
+        assert_eq!(output, "deploying\n✓ deployed\nname\napi\n");
+    }
+
+    #[test]
+    fn colors_are_decided_per_formatter() {
+        let colored_sink = SharedBuffer::default();
+        let plain_sink = SharedBuffer::default();
+        let mut with_colors = crate::new(
+            Format::Plain,
+            crate::Options::default()
+                .with_ascii(false)
+                .with_color(crate::ColorMode::Always)
+                .with_custom_output_target(colored_sink.clone()),
+        );
+        let mut without_colors = crate::new(
+            Format::Plain,
+            crate::Options::default()
+                .with_ascii(false)
+                .with_color(crate::ColorMode::Never)
+                .with_custom_output_target(plain_sink.clone()),
+        );
+
+        with_colors.success(&"deployed");
+        without_colors.success(&"deployed");
+        with_colors.finish();
+        without_colors.finish();
+
+        assert_eq!(colored_sink.into_string(), "\x1b[32m✓\x1b[0m deployed\n");
+        assert_eq!(plain_sink.into_string(), "✓ deployed\n");
+    }
+
+    #[test]
+    fn stripping_colors_keeps_hyperlinks_and_handles_split_writes() {
+        let sink = SharedBuffer::default();
+        let target = crate::ansi::StripColors::wrap(std::sync::Arc::new(std::sync::Mutex::new(
+            sink.clone(),
+        )));
+        let mut target = target.lock().unwrap();
+
+        target.write_all(b"\x1b[3").unwrap();
+        target
+            .write_all(b"2mok\x1b[0m \x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\")
+            .unwrap();
+        target.flush().unwrap();
+
+        assert_eq!(
+            sink.contents(),
+            "ok \x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\"
+        );
+    }
+
+    #[test]
+    fn plain_wraps_fields_with_the_message() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default()
+            .with_color(crate::ColorMode::Never)
+            .with_ascii(false)
+            .with_max_line_length(24)
+            .with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Plain, opts);
+
+        fmt.with_fields(&[("id", &42), ("region", &"us")])
+            .error(&"failed to fetch");
+        fmt.finish();
+
+        let output = sink.into_string();
+        assert!(output.lines().count() > 1);
+        assert!(output.lines().all(|line| line.chars().count() <= 24));
+        assert!(output.contains("region=us"));
+    }
+
+    #[test]
+    fn json_renders_fields_as_object() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default().with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Json, opts);
+
+        fmt.only(vec![Format::Json])
+            .with_fields(&[("id", &42), ("region", &"us")])
+            .error(&"failed to fetch");
+        fmt.with_fields(&[("dropped", &true)])
+            .only(vec![Format::Plain])
+            .println(&"filtered");
+        fmt.println(&"plain");
+        fmt.finish();
+
+        let output = sink.into_string();
+        let records: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["data"], "failed to fetch");
+        assert_eq!(
+            records[0]["fields"],
+            serde_json::json!({"id": 42, "region": "us"})
+        );
+        assert!(records[1].get("fields").is_none());
+    }
+
+    #[test]
+    fn tee_fans_out_and_filters_per_child() {
+        let plain_sink = SharedBuffer::default();
+        let json_sink = SharedBuffer::default();
+        let mut fmt = crate::Tee::new(vec![
+            crate::new(
+                Format::Plain,
+                crate::Options::default()
+                    .with_color(crate::ColorMode::Never)
+                    .with_custom_output_target(plain_sink.clone()),
+            ),
+            crate::new(
+                Format::Json,
+                crate::Options::default().with_custom_output_target(json_sink.clone()),
+            ),
+        ]);
+
+        fmt.println(&"both");
+        {
+            let _guard = fmt.indent();
+            fmt.only(vec![Format::Plain]).println(&"plain only");
+        }
+        fmt.with_fields(&[("id", &1)])
+            .only(vec![Format::Json])
+            .error(&"json only");
+        fmt.println(&"both again");
+        fmt.finish();
+
+        assert_eq!(fmt.format(), Format::Plain);
+        assert_eq!(plain_sink.into_string(), "both\n plain only\nboth again\n");
+
+        let output = json_sink.into_string();
+        let records: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let data: Vec<&str> = records
+            .iter()
+            .map(|record| record["data"].as_str().unwrap())
+            .collect();
+        assert_eq!(data, vec!["both", "json only", "both again"]);
+        assert_eq!(records[1]["fields"]["id"], 1);
+        assert!(records[2].get("fields").is_none());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn tracing_spans_become_indentation() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default()
+            .with_color(crate::ColorMode::Never)
+            .with_custom_output_target(sink.clone());
+        let layer = crate::tracing::PolyfmtLayer::with_formatter(crate::new(Format::Plain, opts));
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        ::tracing::subscriber::with_default(subscriber, || {
+            ::tracing::info!("outside");
+            let span = ::tracing::info_span!("deploy", region = "us");
+            span.in_scope(|| {
+                ::tracing::warn!(id = 42, "inside");
+            });
+            ::tracing::info!("outside again");
+        });
+
+        assert_eq!(
+            sink.into_string(),
+            "outside\n !! inside id=42\noutside again\n"
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn tracing_span_fields_are_included_in_json() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default().with_custom_output_target(sink.clone());
+        let layer = crate::tracing::PolyfmtLayer::with_formatter(crate::new(Format::Json, opts));
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        ::tracing::subscriber::with_default(subscriber, || {
+            let _span = ::tracing::info_span!("deploy", region = "us").entered();
+            ::tracing::error!(id = 42, "failed");
+        });
+
+        let output = sink.into_string();
+        let record: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(record["label"], "error");
+        assert_eq!(record["data"], "failed");
+        assert_eq!(
+            record["fields"],
+            serde_json::json!({"region": "us", "id": 42})
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn tracing_span_fields_reach_json_children_of_a_tee() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let plain_sink = SharedBuffer::default();
+        let json_sink = SharedBuffer::default();
+        let fmt = crate::Tee::new(vec![
+            crate::new(
+                Format::Plain,
+                crate::Options::default()
+                    .with_color(crate::ColorMode::Never)
+                    .with_custom_output_target(plain_sink.clone()),
+            ),
+            crate::new(
+                Format::Json,
+                crate::Options::default().with_custom_output_target(json_sink.clone()),
+            ),
+        ]);
+        let layer = crate::tracing::PolyfmtLayer::with_formatter(Box::new(fmt));
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        ::tracing::subscriber::with_default(subscriber, || {
+            let _span = ::tracing::info_span!("deploy", region = "us").entered();
+            ::tracing::error!(id = 42, "failed");
+        });
+
+        assert_eq!(plain_sink.into_string(), " x failed id=42\n");
+
+        let output = json_sink.into_string();
+        let record: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(
+            record["fields"],
+            serde_json::json!({"region": "us", "id": 42})
+        );
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn log_records_are_routed_through_the_global_formatter() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default().with_custom_output_target(sink.clone());
+        crate::set_global_formatter(crate::new(Format::Json, opts));
+
+        crate::log::init(::log::LevelFilter::Info).unwrap();
+        ::log::warn!(target: "db", "connection slow");
+        ::log::debug!("filtered out by max level");
+
+        let output = sink.into_string();
+        let record: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(record["label"], "warning");
+        assert_eq!(record["data"], "connection slow");
+        assert_eq!(record["fields"]["target"], "db");
+        assert_eq!(record["fields"]["module_path"], module_path!());
+    }
+
+    #[test]
+    fn json_table_is_keyed_by_header() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default().with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Json, opts);
+
+        fmt.table(
+            &["name", "age"],
+            &[vec!["clint".to_string(), "30".to_string()]],
+        );
+        fmt.finish();
+
+        let output = sink.into_string();
+        let record: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(record["label"], "table");
+        assert_eq!(
+            record["data"],
+            serde_json::json!([{"name": "clint", "age": "30"}])
+        );
+    }
+
+    #[test]
+    fn custom_formatters_get_default_methods() {
+        /// Formatter implementing nothing but the methods every formatter has to provide.
+        #[derive(Default)]
+        struct Lines(Vec<String>);
+
+        struct Guard;
+
+        impl crate::IndentGuard for Guard {}
+
+        impl Formatter for Lines {
+            fn print(&mut self, msg: &dyn crate::Displayable) {
+                self.0.push(msg.to_string());
+            }
+            fn println(&mut self, msg: &dyn crate::Displayable) {
+                self.0.push(msg.to_string());
+            }
+            fn error(&mut self, _msg: &dyn crate::Displayable) {}
+            fn success(&mut self, _msg: &dyn crate::Displayable) {}
+            fn warning(&mut self, _msg: &dyn crate::Displayable) {}
+            fn debug(&mut self, msg: &dyn crate::Displayable) {
+                self.0.push(format!("debug: {msg}"));
+            }
+            fn indent(&mut self) -> Box<dyn crate::IndentGuard> {
+                Box::new(Guard)
+            }
+            fn outdent(&mut self) {}
+            fn spacer(&mut self) {}
+            fn pause(&mut self) {}
+            fn resume(&mut self) {}
+            fn question(&mut self, _msg: &dyn crate::Displayable) -> String {
+                String::new()
+            }
+            fn only(&mut self, _types: Vec<Format>) -> &mut dyn Formatter {
+                self
+            }
+            fn finish(&self) {}
+        }
+
+        let mut fmt = Lines::default();
+        fmt.trace(&"tracing");
+        fmt.table(
+            &["name", "status"],
+            &[vec!["api".to_string(), "up".to_string()]],
+        );
+        fmt.with_fields(&[("id", &42)])
+            .with_metadata(&[("target", &"app")])
+            .println(&"done");
+        let progress = fmt.progress(10, &"download");
+        progress.finish();
+        fmt.task(&"build").success(&"built");
+
+        assert_eq!(fmt.format(), Format::Auto);
+        assert_eq!(
+            fmt.0,
+            vec!["debug: tracing", "name  status", "api   up", "done"]
+        );
+    }
+
+    #[test]
+    fn capture_records_typed_events() {
+        let capture = Capture::new().with_answers(["yes"]);
+        let mut fmt: Box<dyn Formatter> = Box::new(capture.clone());
+
+        fmt.println(&"deploying");
+        {
+            let _guard = fmt.indent();
+            fmt.with_fields(&[("id", &42)])
+                .only(vec![Format::Json])
+                .error(&"failed to fetch");
+        }
+        let answer = fmt.question(&"retry? ");
+        fmt.table(&["name"], &[vec!["api".to_string()]]);
+
+        assert_eq!(answer, "yes");
+        capture.assert_messages(&[
+            (EventKind::Println, "deploying"),
+            (EventKind::Error, "failed to fetch"),
+            (EventKind::Question, "retry? "),
+            (EventKind::Table, ""),
+        ]);
+        capture.assert_contains(EventKind::Error, "fetch");
+
+        let events = capture.events();
+        assert_eq!(events[0].depth, 0);
+        assert_eq!(events[1].depth, 1);
+        assert_eq!(events[1].only, vec![Format::Json]);
+        assert_eq!(
+            events[1].fields,
+            vec![("id".to_string(), serde_json::json!(42))]
+        );
+        assert!(events[1].applies_to(&Format::Json));
+        assert!(!events[1].applies_to(&Format::Plain));
+        assert!(events[2].only.is_empty());
+        assert_eq!(events[3].data["rows"], serde_json::json!([["api"]]));
+    }
+
+    #[test]
+    fn capture_ignores_task_updates_after_the_task_finished() {
+        let capture = Capture::new();
+        let mut fmt: Box<dyn Formatter> = Box::new(capture.clone());
+
+        let task = fmt.task(&"deploy");
+        task.success(&"deployed");
+        task.set_message(&"late update");
+        task.error(&"late failure");
+
+        let statuses: Vec<serde_json::Value> = capture
+            .events()
+            .iter()
+            .map(|event| event.data["status"].clone())
+            .collect();
+        assert_eq!(statuses, vec!["started", "success"]);
+    }
+
+    #[test]
+    fn capture_renders_like_a_real_formatter() {
+        let capture = Capture::new();
+        let mut fmt: Box<dyn Formatter> = Box::new(capture.clone());
+
+        fmt.println(&"Hello from polyfmt");
+        {
+            let _guard = fmt.indent();
+            fmt.only(vec![Format::Plain]).println(&"plain only");
+            fmt.only(vec![Format::Json]).println(&"json only");
+        }
+        fmt.with_fields(&[("region", &"us")]).println(&"done");
+
+        assert_eq!(
+            capture.render(
+                Format::Plain,
+                crate::Options::default().with_color(crate::ColorMode::Never)
+            ),
+            "Hello from polyfmt\n plain only\ndone region=us\n"
+        );
+
+        let output = capture.render(Format::Json, crate::Options::default());
+        let records: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[1]["data"], "json only");
+        assert_eq!(records[2]["fields"]["region"], "us");
+    }
+
+    #[test]
+    fn diagnostics_are_routed_to_the_diagnostic_target() {
+        for format in [Format::Plain, Format::Tree, Format::Json] {
+            let output = SharedBuffer::default();
+            let diagnostics = SharedBuffer::default();
+            let opts = crate::Options::default()
+                .with_color(crate::ColorMode::Never)
+                .with_debug(true)
+                .with_custom_output_target(output.clone())
+                .with_custom_diagnostic_target(diagnostics.clone())
+                .with_routing(crate::Routing::diagnostics());
+            let mut fmt = crate::new(format.clone(), opts);
+
+            fmt.println(&"listing");
+            fmt.success(&"done");
+            fmt.error(&"failed");
+            fmt.warning(&"careful");
+            fmt.debug(&"details");
+            fmt.finish();
+
+            let output = output.into_string();
+            let diagnostics = diagnostics.into_string();
+            for msg in ["listing", "done"] {
+                assert!(output.contains(msg), "{format:?}: {msg}");
+                assert!(!diagnostics.contains(msg), "{format:?}: {msg}");
+            }
+            for msg in ["failed", "careful", "details"] {
+                assert!(diagnostics.contains(msg), "{format:?}: {msg}");
+                assert!(!output.contains(msg), "{format:?}: {msg}");
+            }
+        }
+    }
+
+    #[rstest]
+    #[case::plain(Format::Plain)]
+    #[case::tree(Format::Tree)]
+    #[case::json(Format::Json)]
+    fn try_methods_return_write_errors(#[case] format: Format) {
+        let opts = crate::Options::default().with_custom_output_target(FailingWriter);
+        let mut fmt = crate::new(format, opts);
+
+        let err = fmt.try_println(&"lost").unwrap_err();
+        assert_eq!(err.to_string(), "disk full");
+        assert!(fmt.try_error(&"lost").is_err());
+
+        // The infallible methods ignore errors by default.
+        fmt.println(&"lost");
+    }
+
+    #[test]
+    fn record_policy_keeps_the_last_write_error() {
+        let opts = crate::Options::default()
+            .with_custom_output_target(FailingWriter)
+            .with_write_error_policy(crate::WriteErrorPolicy::Record);
+        let mut fmt = crate::new(Format::Plain, opts);
+
+        fmt.println(&"lost");
+
+        let err = fmt.try_finish().unwrap_err();
+        assert_eq!(err.to_string(), "disk full");
+    }
+
+    #[test]
+    #[should_panic(expected = "disk full")]
+    fn panic_policy_panics_on_write_errors() {
+        let opts = crate::Options::default()
+            .with_custom_output_target(FailingWriter)
+            .with_write_error_policy(crate::WriteErrorPolicy::Panic);
+        let mut fmt = crate::new(Format::Json, opts);
+
+        fmt.println(&"lost");
+    }
+
+    #[rstest]
+    #[case::plain(Format::Plain)]
+    #[case::tree(Format::Tree)]
+    #[case::json(Format::Json)]
+    fn closed_pipe_drains_output(#[case] format: Format) {
+        let (reader, writer) = std::io::pipe().unwrap();
+        drop(reader);
+
+        // Even the panic policy shouldn't trip over a closed pipe.
+        let opts = crate::Options::default()
+            .with_custom_output_target(writer)
+            .with_write_error_policy(crate::WriteErrorPolicy::Panic);
+        let mut fmt = crate::new(format, opts);
+
+        fmt.println(&"nobody is listening");
+        fmt.error(&"still nobody");
+        assert!(fmt.try_println(&"drained").is_ok());
+        assert!(fmt.try_finish().is_ok());
+    }
+
+    #[test]
+    fn themes_change_glyphs_and_alignment() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default()
+            .with_color(crate::ColorMode::Never)
+            .with_max_line_length(20)
+            .with_theme(crate::Theme::ascii())
+            .with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Plain, opts);
+
+        fmt.success(&"deployed");
+        fmt.error(&"failed to reach the database");
+        fmt.finish();
+
+        assert_eq!(
+            sink.into_string(),
+            "[ok] deployed\n[x] failed to reach \n    the database\n"
+        );
+
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default()
+            .with_color(crate::ColorMode::Never)
+            .with_theme(crate::Theme::ascii())
+            .with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Tree, opts);
+
+        fmt.println(&"first");
+        {
+            let _guard = fmt.indent();
+            fmt.println(&"nested");
+        }
+        fmt.table(&["name"], &[vec!["api".to_string()]]);
+        fmt.finish();
+
+        assert_eq!(
+            sink.into_string(),
+            "+- first\n+-- nested\n| +------+\n| | name |\n| +------+\n| | api  |\n| +------+\n"
+        );
+    }
+
+    #[rstest]
+    #[case::unix_without_locale(false, vec![("TERM", "xterm-256color")], true)]
+    #[case::utf8_locale(false, vec![("LANG", "en_US.UTF-8")], true)]
+    #[case::lowercase_utf8_locale(false, vec![("LANG", "C.utf8")], true)]
+    #[case::c_locale(false, vec![("LANG", "C")], false)]
+    #[case::latin1_locale(false, vec![("LANG", "de_DE.ISO-8859-1")], false)]
+    #[case::lc_all_wins(false, vec![("LC_ALL", "POSIX"), ("LANG", "en_US.UTF-8")], false)]
+    #[case::empty_lc_all_ignored(false, vec![("LC_ALL", ""), ("LC_CTYPE", "en_US.UTF-8")], true)]
+    #[case::dumb_terminal(false, vec![("TERM", "dumb"), ("LANG", "en_US.UTF-8")], false)]
+    #[case::linux_console(false, vec![("TERM", "linux")], false)]
+    #[case::legacy_windows_console(true, vec![], false)]
+    #[case::windows_over_ssh(true, vec![("TERM", "xterm-256color")], false)]
+    #[case::windows_terminal(true, vec![("WT_SESSION", "8b1d2e3a")], true)]
+    fn unicode_support_detection(
+        #[case] is_windows: bool,
+        #[case] env: Vec<(&str, &str)>,
+        #[case] expected: bool,
+    ) {
+        let lookup = |key: &str| {
+            env.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.to_string())
+        };
+        assert_eq!(crate::theme::supports_unicode(is_windows, lookup), expected);
+    }
+
+    #[test]
+    fn ascii_switch_overrides_detection() {
+        assert_eq!(
+            crate::Options::default().with_ascii(true).theme,
+            crate::Theme::ascii()
+        );
+        assert_eq!(
+            crate::Options::default().with_ascii(false).theme,
+            crate::Theme::default()
+        );
+    }
+
+    #[test]
+    fn links_fall_back_to_text_and_url() {
+        let link = crate::Link::new("build #42", "https://ci.example.com/42");
+        assert_eq!(link.to_string(), "build #42 (https://ci.example.com/42)");
+        assert_eq!(
+            crate::link::render(true, &link),
+            "\x1b]8;;https://ci.example.com/42\x1b\\build #42\x1b]8;;\x1b\\"
+        );
+
+        let bare = crate::Link::new("https://ci.example.com/42", "https://ci.example.com/42");
+        assert_eq!(bare.to_string(), "https://ci.example.com/42");
+    }
+
+    #[test]
+    fn hyperlinks_escape_control_characters() {
+        let link = crate::Link::new(
+            "build\x1b]8;;https://evil.example\x07 #42\n",
+            "https://ci.example.com/a b\x1b\\\x07",
+        );
+        assert_eq!(
+            crate::link::render(true, &link),
+            "\x1b]8;;https://ci.example.com/a%20b%1B\\%07\x1b\\build]8;;https://evil.example #42\x1b]8;;\x1b\\"
+        );
+    }
+
+    #[rstest]
+    #[case::plain(Format::Plain, "build #42 (https://ci.example.com/42)\n")]
+    #[case::tree(Format::Tree, "┌─ build #42 (https://ci.example.com/42)\n")]
+    #[case::json(
+        Format::Json,
+        "{\"data\":{\"text\":\"build #42\",\"url\":\"https://ci.example.com/42\"},\"depth\":0,\"groups\":[],\"label\":\"info\"}\n"
+    )]
+    fn links_render_per_format(#[case] format: Format, #[case] expected: &str) {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default()
+            .with_color(crate::ColorMode::Never)
+            .with_ascii(false)
+            .with_custom_output_target(sink.clone());
+        let is_json = format == Format::Json;
+        let mut fmt = crate::new(format, opts);
+
+        fmt.println(&crate::Link::new("build #42", "https://ci.example.com/42"));
+        fmt.finish();
+
+        let output = sink.into_string();
+        if is_json {
+            assert_eq!(strip_metadata(&output), expected);
+        } else {
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[rstest]
+    #[case::not_a_terminal(false, vec![("TERM_PROGRAM", "iTerm.app")], false)]
+    #[case::unknown_terminal(true, vec![("TERM", "xterm-256color")], false)]
+    #[case::iterm(true, vec![("TERM_PROGRAM", "iTerm.app")], true)]
+    #[case::windows_terminal(true, vec![("WT_SESSION", "8b1d2e3a")], true)]
+    #[case::kitty(true, vec![("TERM", "xterm-kitty")], true)]
+    #[case::recent_vte(true, vec![("VTE_VERSION", "6003")], true)]
+    #[case::old_vte(true, vec![("VTE_VERSION", "4601")], false)]
+    #[case::ci_log_viewer(true, vec![("CI", "true"), ("TERM_PROGRAM", "vscode")], false)]
+    #[case::forced_on(false, vec![("FORCE_HYPERLINK", "1")], true)]
+    #[case::forced_off(true, vec![("FORCE_HYPERLINK", "0"), ("WT_SESSION", "8b1d2e3a")], false)]
+    fn hyperlink_support_detection(
+        #[case] is_terminal: bool,
+        #[case] env: Vec<(&str, &str)>,
+        #[case] expected: bool,
+    ) {
+        let lookup = |key: &str| {
+            env.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.to_string())
+        };
+        assert_eq!(
+            crate::link::supports_hyperlinks(is_terminal, lookup),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case::default(0, 0, crate::Verbosity::Info)]
+    #[case::quiet(1, 0, crate::Verbosity::Warn)]
+    #[case::very_quiet(2, 0, crate::Verbosity::Error)]
+    #[case::quieter_than_error(5, 0, crate::Verbosity::Error)]
+    #[case::verbose(0, 1, crate::Verbosity::Debug)]
+    #[case::very_verbose(0, 2, crate::Verbosity::Trace)]
+    #[case::louder_than_trace(0, 7, crate::Verbosity::Trace)]
+    #[case::flags_cancel_out(1, 1, crate::Verbosity::Info)]
+    fn verbosity_from_flags(
+        #[case] quiet: u8,
+        #[case] verbose: u8,
+        #[case] expected: crate::Verbosity,
+    ) {
+        assert_eq!(crate::Verbosity::from_flags(quiet, verbose), expected);
+    }
+
+    #[rstest]
+    #[case::error(crate::Verbosity::Error, "x failed\n")]
+    #[case::warn(crate::Verbosity::Warn, "x failed\n!! careful\n")]
+    #[case::info(crate::Verbosity::Info, "hello\nx failed\n!! careful\n✓ done\n\n")]
+    #[case::trace(
+        crate::Verbosity::Trace,
+        "hello\nx failed\n!! careful\n✓ done\n\n[debug] details\n[trace] internals\n"
+    )]
+    fn verbosity_filters_messages(#[case] verbosity: crate::Verbosity, #[case] expected: &str) {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default()
+            .with_color(crate::ColorMode::Never)
+            .with_ascii(false)
+            .with_verbosity(verbosity)
+            .with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Plain, opts);
+
+        fmt.println(&"hello");
+        fmt.error(&"failed");
+        fmt.warning(&"careful");
+        fmt.success(&"done");
+        fmt.spacer();
+        fmt.debug(&"details");
+        fmt.trace(&"internals");
+        fmt.finish();
+
+        assert_eq!(sink.into_string(), expected);
+    }
+
+    #[test]
+    fn json_labels_trace_records() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default()
+            .with_verbosity(crate::Verbosity::Trace)
+            .with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Json, opts);
+
+        fmt.debug(&"details");
+        fmt.trace(&"internals");
+        fmt.finish();
+
+        let labels: Vec<String> = sink
+            .into_string()
+            .lines()
+            .map(|line| {
+                serde_json::from_str::<serde_json::Value>(line).unwrap()["label"].to_string()
+            })
+            .collect();
+        assert_eq!(labels, vec!["\"debug\"", "\"trace\""]);
+    }
+
+    #[test]
+    fn json_records_nesting() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default().with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Json, opts);
+
+        fmt.println(&"root");
+        {
+            let _outer = fmt.indent();
+            fmt.println(&"first");
+            {
+                let _inner = fmt.indent();
+                fmt.success(&"nested");
+            }
+            let _sibling = fmt.indent();
+            fmt.warning(&"sibling");
+        }
+        fmt.println(&"back at root");
+        fmt.finish();
+
+        let records: Vec<serde_json::Value> = strip_metadata(&sink.into_string())
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(
+            records,
+            vec![
+                serde_json::json!({"label": "info", "data": "root", "depth": 0, "groups": []}),
+                serde_json::json!({"label": "info", "data": "first", "depth": 1, "groups": [1]}),
+                serde_json::json!({"label": "success", "data": "nested", "depth": 2, "groups": [1, 2]}),
+                serde_json::json!({"label": "warning", "data": "sibling", "depth": 2, "groups": [1, 3]}),
+                serde_json::json!({"label": "info", "data": "back at root", "depth": 0, "groups": []}),
+            ]
+        );
+    }
+
+    #[test]
+    fn json_guards_dropped_out_of_order_close_their_own_group() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default().with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Json, opts);
+
+        let outer = fmt.indent();
+        let inner = fmt.indent();
+        drop(outer);
+        fmt.println(&"still inside");
+        drop(inner);
+        fmt.println(&"back at root");
+        fmt.finish();
+
+        let records: Vec<serde_json::Value> = strip_metadata(&sink.into_string())
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(
+            records,
+            vec![
+                serde_json::json!({"label": "info", "data": "still inside", "depth": 1, "groups": [2]}),
+                serde_json::json!({"label": "info", "data": "back at root", "depth": 0, "groups": []}),
+            ]
+        );
+    }
+
+    #[test]
+    fn json_pretty_prints_records() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default()
+            .with_json_pretty(true)
+            .with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Json, opts);
+
+        fmt.println(&"hello");
+        fmt.finish();
+
+        let output = sink.into_string();
+        assert!(
+            output.starts_with("{\n  \"data\": \"hello\",\n  \"depth\": 0,\n  \"elapsed_ms\": ")
+        );
+        assert!(
+            output.contains(
+                ",\n  \"groups\": [],\n  \"label\": \"info\",\n  \"schema_version\": 1,\n"
+            )
+        );
+        assert_eq!(
+            strip_metadata(&output),
+            "{\"data\":\"hello\",\"depth\":0,\"groups\":[],\"label\":\"info\"}\n"
+        );
+    }
+
+    #[rstest]
+    #[case::array(
+        crate::JsonMode::Array,
+        r#"[{"data":"hello","depth":0,"groups":[],"label":"info"},{"data":"careful","depth":0,"groups":[],"label":"warning"}]"#
+    )]
+    #[case::document(
+        crate::JsonMode::Document,
+        r#"{"messages":[{"data":"careful","depth":0,"groups":[],"label":"warning"}],"results":[{"data":"hello","depth":0,"groups":[],"label":"info"}]}"#
+    )]
+    fn json_collects_records_into_one_document(
+        #[case] mode: crate::JsonMode,
+        #[case] expected: &str,
+    ) {
+        let sink = SharedBuffer::default();
+        let diagnostics = SharedBuffer::default();
+        let opts = crate::Options::default()
+            .with_json_mode(mode)
+            .with_routing(crate::Routing {
+                error: crate::Destination::Diagnostic,
+                ..crate::Routing::default()
+            })
+            .with_custom_output_target(sink.clone())
+            .with_custom_diagnostic_target(diagnostics.clone());
+        let mut fmt = crate::new(Format::Json, opts);
+
+        fmt.println(&"hello");
+        fmt.warning(&"careful");
+        fmt.error(&"streamed");
+        assert_eq!(sink.contents(), "");
+        assert_eq!(
+            strip_metadata(&diagnostics.contents()),
+            "{\"data\":\"streamed\",\"depth\":0,\"groups\":[],\"label\":\"error\"}\n"
+        );
+
+        fmt.finish();
+        fmt.finish();
+
+        let output = sink.into_string();
+        assert_eq!(output.lines().count(), 1);
+        assert_eq!(strip_metadata(&output), format!("{expected}\n"));
+    }
+
+    #[test]
+    fn json_document_is_written_when_the_program_bails_out() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default()
+            .with_json_mode(crate::JsonMode::Array)
+            .with_custom_output_target(sink.clone());
+
+        let result = std::panic::catch_unwind(move || {
+            let mut fmt = crate::new(Format::Json, opts);
+            fmt.println(&"started");
+            panic!("something went wrong");
+        });
+        assert!(result.is_err());
+
+        let records: serde_json::Value =
+            serde_json::from_str(&strip_metadata(&sink.into_string())).unwrap();
+        assert_eq!(
+            records,
+            serde_json::json!([{"data": "started", "depth": 0, "groups": [], "label": "info"}])
+        );
+
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default()
+            .with_json_mode(crate::JsonMode::Document)
+            .with_custom_output_target(sink.clone());
+        drop(crate::new(Format::Json, opts));
+
+        assert_eq!(sink.into_string(), "{\"messages\":[],\"results\":[]}\n");
+    }
+
+    #[test]
+    fn json_questions_are_written_before_the_answer_is_read() {
+        /// Answers the question, remembering what had been written to the diagnostic target by then.
+        struct Answer {
+            diagnostics: SharedBuffer,
+            written_before_reading: Option<String>,
+        }
+
+        impl std::io::Read for Answer {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.written_before_reading = Some(self.diagnostics.contents());
+                std::io::Read::read(&mut &b"yes\n"[..], buf)
+            }
+        }
+
+        let sink = SharedBuffer::default();
+        let diagnostics = SharedBuffer::default();
+        let opts = crate::Options::default()
+            .with_json_mode(crate::JsonMode::Array)
+            .with_custom_output_target(sink.clone())
+            .with_custom_diagnostic_target(diagnostics.clone());
+        let fmt = crate::json::Json::new(opts, crate::json::Encoding::Json);
+
+        let mut input = std::io::BufReader::new(Answer {
+            diagnostics: diagnostics.clone(),
+            written_before_reading: None,
+        });
+        let answer = fmt.lock().unwrap().ask(&"continue?", &mut input).unwrap();
+        Formatter::finish(&fmt);
+
+        assert_eq!(answer, "yes");
+        assert_eq!(
+            strip_metadata(&input.into_inner().written_before_reading.unwrap()),
+            "{\"data\":\"continue?\",\"depth\":0,\"groups\":[],\"label\":\"question\"}\n"
+        );
+        assert_eq!(sink.into_string(), "[]\n");
+    }
+
+    #[test]
+    fn json_records_printed_after_the_document_go_to_the_diagnostic_target() {
+        let sink = SharedBuffer::default();
+        let diagnostics = SharedBuffer::default();
+        let opts = crate::Options::default()
+            .with_json_mode(crate::JsonMode::Array)
+            .with_custom_output_target(sink.clone())
+            .with_custom_diagnostic_target(diagnostics.clone());
+
+        let mut fmt = crate::new(Format::Json, opts);
+        fmt.println(&"started");
+        fmt.finish();
+        fmt.println(&"too late");
+        fmt.finish();
+        drop(fmt);
+
+        let records: serde_json::Value =
+            serde_json::from_str(&strip_metadata(&sink.into_string())).unwrap();
+        assert_eq!(
+            records,
+            serde_json::json!([{"data": "started", "depth": 0, "groups": [], "label": "info"}])
+        );
+        assert_eq!(
+            strip_metadata(&diagnostics.into_string()),
+            "{\"data\":\"too late\",\"depth\":0,\"groups\":[],\"label\":\"info\"}\n"
+        );
+    }
+
+    #[rstest]
+    #[case::zero(std::time::Duration::ZERO, "    0.000s")]
+    #[case::millis(std::time::Duration::from_millis(1234), "    1.234s")]
+    #[case::hours(std::time::Duration::from_secs(7200), " 7200.000s")]
+    fn elapsed_timestamps_stay_aligned(
+        #[case] elapsed: std::time::Duration,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(crate::timestamp::format_elapsed(elapsed), expected);
+    }
+
+    #[test]
+    fn timestamps_prefix_every_line() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default()
+            .with_color(crate::ColorMode::Never)
+            .with_timestamps(crate::Timestamps::Rfc3339)
+            .with_max_line_length(40)
+            .with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Plain, opts);
+
+        fmt.print(&"partial ");
+        fmt.println(&"line");
+        fmt.println(&"a message long enough to wrap onto a second line");
+        fmt.finish();
+
+        let output = sink.into_string();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 5, "{output}");
+        for line in &lines {
+            let (stamp, _) = line.split_once(' ').unwrap();
+            assert!(humantime::parse_rfc3339(stamp).is_ok(), "{line}");
+            assert!(line.len() <= 40, "{line}");
+        }
+        assert!(lines[0].ends_with(" partial line"));
+
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default()
+            .with_color(crate::ColorMode::Never)
+            .with_timestamps(crate::Timestamps::Elapsed)
+            .with_ascii(false)
+            .with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Tree, opts);
+
+        fmt.println(&"step");
+        fmt.finish();
+
+        let output = sink.into_string();
+        let (elapsed, line) = output.trim_start().split_once("s ").unwrap();
+        assert!(elapsed.parse::<f64>().is_ok(), "{output}");
+        assert_eq!(line, "┌─ step\n");
+    }
+
+    #[test]
+    fn json_records_always_carry_timestamps() {
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default().with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Json, opts);
+
+        fmt.println(&"hello");
+        fmt.finish();
+
+        let record: serde_json::Value = serde_json::from_str(&sink.into_string()).unwrap();
+        assert!(humantime::parse_rfc3339(record["ts"].as_str().unwrap()).is_ok());
+        assert!(record["elapsed_ms"].is_u64());
+    }
+
+    #[test]
+    fn json_output_matches_schema() {
+        let validator = jsonschema::validator_for(&crate::schema::json_schema()).unwrap();
+
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default()
+            .with_verbosity(crate::Verbosity::Trace)
+            .with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Json, opts);
+
+        fmt.println(&"info");
+        fmt.with_fields(&[("id", &42), ("region", &"us")])
+            .success(&"success");
+        fmt.warning(&"warning");
+        fmt.error(&"error");
+        {
+            let _guard = fmt.indent();
+            fmt.debug(&"debug");
+            fmt.trace(&crate::Link::new("trace", "https://example.com"));
+        }
+        fmt.table(&["name", "status"], &[vec!["api".to_string()]]);
+        let progress = fmt.progress(2, &"download");
+        progress.finish();
+        let task = fmt.task(&"build");
+        task.set_message(&"compiling");
+        task.error(&"failed");
+        fmt.finish();
+
+        // Questions wait for an answer on stdin, so their record is built by hand.
+        let question = crate::schema::Record {
+            schema_version: crate::schema::SCHEMA_VERSION,
+            event: crate::schema::Event::Question(crate::schema::Message {
+                data: serde_json::json!("continue?"),
+                fields: None,
+            }),
+            ts: "2024-05-01T09:30:00.123Z".to_string(),
+            elapsed_ms: 0,
+            depth: 0,
+            groups: Vec::new(),
+        };
+
+        let output = sink.into_string() + &serde_json::to_string(&question).unwrap();
+        let mut labels = Vec::new();
+        for line in output.lines() {
+            let record: serde_json::Value = serde_json::from_str(line).unwrap();
+            let errors: Vec<String> = validator
+                .iter_errors(&record)
+                .map(|err| err.to_string())
+                .collect();
+            assert!(errors.is_empty(), "{line}: {errors:?}");
+
+            let parsed: crate::schema::Record = serde_json::from_value(record.clone()).unwrap();
+            assert_eq!(serde_json::to_value(parsed).unwrap(), record);
+            labels.push(record["label"].as_str().unwrap().to_string());
+        }
+
+        assert_eq!(
+            labels,
+            vec![
+                "info", "success", "warning", "error", "debug", "trace", "table", "progress",
+                "progress", "task", "task", "task", "question"
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case::unknown_version(serde_json::json!({"schema_version": 2, "label": "info", "data": "x", "ts": "2024-05-01T09:30:00.123Z", "elapsed_ms": 0}))]
+    #[case::missing_data(serde_json::json!({"schema_version": 1, "label": "info", "ts": "2024-05-01T09:30:00.123Z", "elapsed_ms": 0}))]
+    #[case::unknown_task_status(serde_json::json!({"schema_version": 1, "label": "task", "task": "build", "status": "paused", "data": "x", "ts": "2024-05-01T09:30:00.123Z", "elapsed_ms": 0}))]
+    fn schema_rejects_malformed_records(#[case] record: serde_json::Value) {
+        let schema = crate::schema::json_schema();
+        assert!(!jsonschema::is_valid(&schema, &record));
+    }
+
+    #[rstest]
+    #[case::array(crate::JsonMode::Array)]
+    #[case::document(crate::JsonMode::Document)]
+    fn collected_json_output_matches_schema(#[case] mode: crate::JsonMode) {
+        let schema = crate::schema::json_schema_for(mode);
+
+        let sink = SharedBuffer::default();
+        let opts = crate::Options::default()
+            .with_json_mode(mode)
+            .with_custom_output_target(sink.clone());
+        let mut fmt = crate::new(Format::Json, opts);
+
+        fmt.println(&"info");
+        {
+            let _guard = fmt.indent();
+            fmt.with_fields(&[("id", &42)]).warning(&"warning");
+        }
+        fmt.table(&["name"], &[vec!["api".to_string()]]);
+        fmt.finish();
+
+        let output: serde_json::Value = serde_json::from_str(&sink.into_string()).unwrap();
+        let errors: Vec<String> = jsonschema::validator_for(&schema)
+            .unwrap()
+            .iter_errors(&output)
+            .map(|err| err.to_string())
+            .collect();
+        assert!(errors.is_empty(), "{output}: {errors:?}");
+
+        // The collected output isn't a single record, so it needs its own schema.
+        assert!(!jsonschema::is_valid(
+            &crate::schema::json_schema(),
+            &output
+        ));
+        if mode == crate::JsonMode::Document {
+            let document: crate::schema::Document = serde_json::from_value(output).unwrap();
+            assert_eq!(document.results.len(), 2);
+            assert_eq!(document.messages.len(), 1);
+        }
+    }
+
+    #[rstest]
+    #[case::record(crate::JsonMode::Lines, "record")]
+    #[case::array(crate::JsonMode::Array, "array")]
+    #[case::document(crate::JsonMode::Document, "document")]
+    fn published_schema_is_up_to_date(#[case] mode: crate::JsonMode, #[case] name: &str) {
+        let path = format!("{}/schema/{name}.schema.json", env!("CARGO_MANIFEST_DIR"));
+        let generated =
+            serde_json::to_string_pretty(&crate::schema::json_schema_for(mode)).unwrap() + "\n";
+
+        if std::env::var_os("UPDATE_SCHEMA").is_some() {
+            std::fs::write(&path, &generated).unwrap();
+        }
+
+        let published = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            published == generated,
+            "schema/{name}.schema.json is out of date, regenerate it with `UPDATE_SCHEMA=1 cargo test published_schema`"
+        );
+    }
+
+    /// Removes the keys every Json record carries (the schema version and the `ts` and `elapsed_ms` keys, which
+    /// differ on every run) from Json output and writes every value back on a single line.
+    fn strip_metadata(output: &str) -> String {
+        fn strip(value: &mut serde_json::Value) {
+            match value {
+                serde_json::Value::Object(map) => {
+                    map.remove("schema_version");
+                    map.remove("ts");
+                    map.remove("elapsed_ms");
+                    map.values_mut().for_each(strip);
+                }
+                serde_json::Value::Array(values) => values.iter_mut().for_each(strip),
+                _ => {}
+            }
+        }
+
+        serde_json::Deserializer::from_str(output)
+            .into_iter::<serde_json::Value>()
+            .map(|value| {
+                let mut value = value.unwrap();
+                strip(&mut value);
+                format!("{value}\n")
+            })
+            .collect()
+    }
+
+    fn lookup<'a>(env: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        |key: &str| {
+            env.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.to_string())
+        }
+    }
+
+    #[test]
+    fn options_from_env_reads_prefixed_variables() {
+        let opts = crate::env::options_from_env("MYAPP", lookup(&[])).unwrap();
+        assert_eq!(opts.verbosity, crate::Verbosity::Info);
+        assert_eq!(opts.color, crate::ColorMode::Auto);
+        assert_eq!(opts.padding, 0);
+
+        let env = [
+            ("MYAPP_VERBOSITY", "Trace"),
+            ("MYAPP_COLOR", "never"),
+            ("MYAPP_MAX_LINE_LENGTH", "72"),
+            ("MYAPP_PADDING", " 4 "),
+            ("MYAPP_TIMESTAMPS", "Elapsed"),
+            ("OTHERAPP_PADDING", "8"),
+        ];
+        let opts = crate::env::options_from_env("MYAPP_", lookup(&env)).unwrap();
+        assert_eq!(opts.verbosity, crate::Verbosity::Trace);
+        assert_eq!(opts.color, crate::ColorMode::Never);
+        assert_eq!(opts.max_line_length, 72);
+        assert_eq!(opts.padding, 4);
+        assert_eq!(opts.timestamps, crate::Timestamps::Elapsed);
+    }
+
+    #[rstest]
+    #[case::debug_flag(vec![("MYAPP_DEBUG", "yes")], crate::Verbosity::Debug, crate::ColorMode::Auto)]
+    #[case::verbosity_beats_debug(vec![("MYAPP_DEBUG", "1"), ("MYAPP_VERBOSITY", "warn")], crate::Verbosity::Warn, crate::ColorMode::Auto)]
+    #[case::empty_values_are_unset(vec![("MYAPP_VERBOSITY", ""), ("MYAPP_DEBUG", "true")], crate::Verbosity::Debug, crate::ColorMode::Auto)]
+    #[case::no_color(vec![("NO_COLOR", "1")], crate::Verbosity::Info, crate::ColorMode::Never)]
+    #[case::clicolor_force(vec![("CLICOLOR_FORCE", "1")], crate::Verbosity::Info, crate::ColorMode::Always)]
+    #[case::color_beats_no_color(vec![("NO_COLOR", "1"), ("MYAPP_COLOR", "always")], crate::Verbosity::Info, crate::ColorMode::Always)]
+    fn options_from_env_precedence(
+        #[case] env: Vec<(&str, &str)>,
+        #[case] verbosity: crate::Verbosity,
+        #[case] color: crate::ColorMode,
+    ) {
+        let opts = crate::env::options_from_env("MYAPP", lookup(&env)).unwrap();
+        assert_eq!(opts.verbosity, verbosity);
+        assert_eq!(opts.color, color);
+    }
+
+    #[rstest]
+    #[case::verbosity(("MYAPP_VERBOSITY", "loud"), "invalid value \"loud\" for MYAPP_VERBOSITY: expected one of error, warn, info, debug or trace")]
+    #[case::debug(("MYAPP_DEBUG", "maybe"), "invalid value \"maybe\" for MYAPP_DEBUG: expected one of 1, true, yes, on, 0, false, no or off")]
+    #[case::color(("MYAPP_COLOR", "sometimes"), "invalid value \"sometimes\" for MYAPP_COLOR: expected one of auto, always or never")]
+    #[case::max_line_length(("MYAPP_MAX_LINE_LENGTH", "0"), "invalid value \"0\" for MYAPP_MAX_LINE_LENGTH: expected a positive number")]
+    #[case::padding(("MYAPP_PADDING", "-1"), "invalid value \"-1\" for MYAPP_PADDING: expected a number between 0 and 65535")]
+    #[case::timestamps(("MYAPP_TIMESTAMPS", "utc"), "invalid value \"utc\" for MYAPP_TIMESTAMPS: expected one of off, rfc3339 or elapsed")]
+    #[case::output(("MYAPP_OUTPUT", "/nonexistent/polyfmt/out.log"), "could not create output file /nonexistent/polyfmt/out.log from MYAPP_OUTPUT: No such file or directory (os error 2)")]
+    fn options_from_env_rejects_invalid_values(
+        #[case] variable: (&str, &str),
+        #[case] expected: &str,
+    ) {
+        let env = [variable];
+        let err = crate::env::options_from_env("MYAPP", lookup(&env))
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), expected);
+    }
+
+    #[test]
+    fn options_from_env_writes_to_output_file() {
+        let path = std::env::temp_dir().join(format!("polyfmt-env-{}.log", std::process::id()));
+        let path_str = path.to_string_lossy().to_string();
+        let env = [
+            ("MYAPP_OUTPUT", path_str.as_str()),
+            ("MYAPP_COLOR", "never"),
+        ];
+
+        let opts = crate::env::options_from_env("MYAPP", lookup(&env)).unwrap();
+        let mut fmt = crate::new(Format::Plain, opts);
+        fmt.println(&"to the file");
+        fmt.finish();
+        drop(fmt);
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "to the file\n");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[rstest]
+    #[case::unset(vec![], Format::Auto)]
+    #[case::set(vec![("MYAPP_FORMAT", "JSON")], Format::Json)]
+    #[case::empty(vec![("MYAPP_FORMAT", " ")], Format::Auto)]
+    fn format_from_env(#[case] env: Vec<(&str, &str)>, #[case] expected: Format) {
+        assert_eq!(
+            crate::env::format_from_env("MYAPP", lookup(&env)).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn format_from_env_rejects_unknown_formats() {
+        let env = [("MYAPP_FORMAT", "fancy")];
+        let err = crate::env::format_from_env("MYAPP", lookup(&env)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid value \"fancy\" for MYAPP_FORMAT: expected one of plain, tree, spinner, json, yaml, silent or auto"
+        );
+    }
+
+    #[cfg(feature = "clap")]
+    #[derive(clap::Parser)]
+    struct Cli {
+        #[command(flatten)]
+        output: crate::cli::OutputArgs,
+    }
+
+    #[cfg(feature = "clap")]
+    #[rstest]
+    #[case::defaults(vec![], None, false, crate::Verbosity::Info)]
+    #[case::format(vec!["--format", "json"], Some(Format::Json), false, crate::Verbosity::Info)]
+    #[case::format_ignores_case(vec!["--format=TREE"], Some(Format::Tree), false, crate::Verbosity::Info)]
+    #[case::no_color(vec!["--no-color"], None, true, crate::Verbosity::Info)]
+    #[case::debug(vec!["--debug"], None, false, crate::Verbosity::Debug)]
+    #[case::trace(vec!["-vv"], None, false, crate::Verbosity::Trace)]
+    #[case::debug_and_verbose(vec!["--debug", "-vv"], None, false, crate::Verbosity::Trace)]
+    #[case::quiet(vec!["-q"], None, false, crate::Verbosity::Warn)]
+    #[case::very_quiet(vec!["--quiet", "--quiet"], None, false, crate::Verbosity::Error)]
+    fn clap_output_args(
+        #[case] args: Vec<&str>,
+        #[case] format: Option<Format>,
+        #[case] no_color: bool,
+        #[case] verbosity: crate::Verbosity,
+    ) {
+        use clap::Parser;
+
+        let cli = Cli::try_parse_from(std::iter::once("mytool").chain(args)).unwrap();
+        assert_eq!(cli.output.format, format);
+        assert_eq!(cli.output.no_color, no_color);
+
+        let opts = cli.output.apply(crate::Options::default());
+        assert_eq!(opts.verbosity, verbosity);
+        let color = if no_color {
+            crate::ColorMode::Never
+        } else {
+            crate::ColorMode::Auto
+        };
+        assert_eq!(opts.color, color);
+    }
+
+    #[cfg(feature = "clap")]
+    #[test]
+    fn clap_output_args_keep_unset_options() {
+        use clap::Parser;
+
+        let cli = Cli::try_parse_from(["mytool"]).unwrap();
+        let opts = cli
+            .output
+            .apply(crate::Options::default().with_verbosity(crate::Verbosity::Trace));
+        assert_eq!(opts.verbosity, crate::Verbosity::Trace);
+    }
+
+    #[cfg(feature = "clap")]
+    #[test]
+    fn clap_output_args_reject_unknown_formats() {
+        use clap::Parser;
+
+        let err = Cli::try_parse_from(["mytool", "--format", "fancy"])
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("invalid value 'fancy' for '--format <FORMAT>'"));
+        assert!(err.contains("[possible values: plain, tree, spinner, json, yaml, silent, auto]"));
+
+        assert!(Cli::try_parse_from(["mytool", "-q", "-v"]).is_err());
+    }
+
+    #[cfg(feature = "clap")]
+    #[rstest]
+    #[case::env_format(vec![], vec![("MYAPP_FORMAT", "json")], Format::Json)]
+    #[case::flag_beats_env(vec!["--format", "silent"], vec![("MYAPP_FORMAT", "json")], Format::Silent)]
+    fn clap_output_args_fall_back_to_the_environment(
+        #[case] args: Vec<&str>,
+        #[case] env: Vec<(&str, &str)>,
+        #[case] expected: Format,
+    ) {
+        use clap::Parser;
+
+        let cli = Cli::try_parse_from(std::iter::once("mytool").chain(args)).unwrap();
+        let fmt = cli.output.formatter_from("MYAPP", lookup(&env)).unwrap();
+        assert_eq!(fmt.format(), expected);
+    }
+
+    #[cfg(feature = "clap")]
+    #[test]
+    fn clap_output_args_build_a_formatter() {
+        use clap::Parser;
+
+        let sink = SharedBuffer::default();
+        let cli = Cli::try_parse_from(["mytool", "--format", "json", "-q"]).unwrap();
+        let mut fmt = cli
+            .output
+            .formatter_with(crate::Options::default().with_custom_output_target(sink.clone()));
+
+        fmt.println(&"hidden by --quiet");
+        fmt.warning(&"shown");
+        fmt.finish();
+
+        assert_eq!(
+            strip_metadata(&sink.into_string()),
+            "{\"data\":\"shown\",\"depth\":0,\"groups\":[],\"label\":\"warning\"}\n"
+        );
+    }
 }
@@ -0,0 +1,174 @@
+use crate::{Destination, Displayable, OutputTarget, OutputTargetKind};
+use serde::Serialize;
+use std::{
+    cell::Cell,
+    fmt::{self, Display, Write},
+    io::IsTerminal,
+};
+
+thread_local! {
+    /// Set while a formatter renders a message for a target which can display hyperlinks.
+    static HYPERLINKS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Text pointing to a URL, like a link to a dashboard or build log.
+///
+/// The human readable formats turn it into a clickable [OSC 8](https://gist.github.com/egmontkob/eb114294efbcd5adb1944c9f3cb5feda)
+/// hyperlink when writing to a terminal that supports them and print it as `text (url)` everywhere else. The Json
+/// format writes it as `{"text": .., "url": ..}`. Hyperlinks percent-encode control characters and spaces in the URL
+/// and drop control characters from the text, so neither can break out of the escape sequence.
+///
+/// ```rust
+/// # use polyfmt::{new, Format, Link, Options};
+/// let mut fmt = new(Format::Plain, Options::default());
+/// fmt.println(&Link::new("build #42", "https://ci.example.com/builds/42"));
+/// ```
+///
+/// Only links rendered by the formatter can become hyperlinks; a link that was already turned into a string with
+/// `format!` always uses the fallback.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Link {
+    pub text: String,
+    pub url: String,
+}
+
+impl Link {
+    pub fn new(text: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            url: url.into(),
+        }
+    }
+}
+
+impl Display for Link {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if HYPERLINKS.get() {
+            return write!(
+                f,
+                "\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\",
+                escape_url(&self.url),
+                strip_control_characters(&self.text)
+            );
+        }
+
+        if self.text.is_empty() || self.text == self.url {
+            return write!(f, "{}", self.url);
+        }
+
+        write!(f, "{} ({})", self.text, self.url)
+    }
+}
+
+/// Percent-encodes every byte of a URL that isn't printable ASCII, so that control characters can't end the
+/// hyperlink's escape sequence early or smuggle in escape sequences of their own.
+fn escape_url(url: &str) -> String {
+    let mut escaped = String::with_capacity(url.len());
+    for byte in url.bytes() {
+        if byte.is_ascii_graphic() {
+            escaped.push(char::from(byte));
+        } else {
+            let _ = write!(escaped, "%{byte:02X}");
+        }
+    }
+
+    escaped
+}
+
+/// Removes control characters from the text of a hyperlink for the same reason.
+fn strip_control_characters(text: &str) -> String {
+    text.chars().filter(|c| !c.is_control()).collect()
+}
+
+/// Whether the targets of a formatter can display hyperlinks.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Hyperlinks {
+    output: bool,
+    diagnostic: bool,
+}
+
+impl Hyperlinks {
+    pub(crate) fn detect(output_target: &OutputTarget, diagnostic_target: &OutputTarget) -> Self {
+        let env = |key: &str| std::env::var(key).ok();
+        let terminal = |target: &OutputTarget| match target.kind {
+            OutputTargetKind::Stdout => std::io::stdout().is_terminal(),
+            OutputTargetKind::Stderr => std::io::stderr().is_terminal(),
+            OutputTargetKind::Custom => false,
+        };
+
+        Self {
+            output: supports_hyperlinks(terminal(output_target), env),
+            diagnostic: supports_hyperlinks(terminal(diagnostic_target), env),
+        }
+    }
+
+    /// Renders a message for the given destination, turning any [`Link`] in it into a hyperlink if the target can
+    /// display it.
+    pub(crate) fn render(&self, destination: Destination, msg: &dyn Displayable) -> String {
+        let enabled = match destination {
+            Destination::Output => self.output,
+            Destination::Diagnostic => self.diagnostic,
+        };
+
+        render(enabled, msg)
+    }
+}
+
+/// Renders a message with hyperlinks turned on or off.
+pub(crate) fn render(hyperlinks: bool, msg: &dyn Displayable) -> String {
+    let previous = HYPERLINKS.replace(hyperlinks);
+    let rendered = msg.to_string();
+    HYPERLINKS.set(previous);
+    rendered
+}
+
+/// Values of `TERM_PROGRAM` set by terminals which support hyperlinks.
+const HYPERLINK_TERM_PROGRAMS: &[&str] = &[
+    "iTerm.app",
+    "WezTerm",
+    "vscode",
+    "ghostty",
+    "Hyper",
+    "terminology",
+];
+
+/// Prefixes of `TERM` set by terminals which support hyperlinks.
+const HYPERLINK_TERMS: &[&str] = &["xterm-kitty", "alacritty", "foot", "xterm-ghostty"];
+
+/// Guesses whether the terminal can display OSC 8 hyperlinks. Terminals which don't understand them usually drop
+/// the escape sequences and show the text, but some print them as garbage, so only terminals known to support them
+/// get hyperlinks. Takes the environment as a function so it can be tested.
+pub(crate) fn supports_hyperlinks(is_terminal: bool, env: impl Fn(&str) -> Option<String>) -> bool {
+    if let Some(value) = env("FORCE_HYPERLINK") {
+        return value.trim() != "0";
+    }
+
+    if !is_terminal || env("CI").is_some() {
+        return false;
+    }
+
+    if env("WT_SESSION").is_some() || env("KONSOLE_VERSION").is_some() || env("DOMTERM").is_some() {
+        return true;
+    }
+
+    if let Some(version) = env("VTE_VERSION")
+        && version
+            .trim()
+            .parse::<u32>()
+            .is_ok_and(|version| version >= 5000)
+    {
+        return true;
+    }
+
+    if env("TERM_PROGRAM")
+        .is_some_and(|program| HYPERLINK_TERM_PROGRAMS.contains(&program.as_str()))
+    {
+        return true;
+    }
+
+    env("TERM").is_some_and(|term| {
+        HYPERLINK_TERMS
+            .iter()
+            .any(|supported| term.starts_with(supported))
+    })
+}
@@ -0,0 +1,73 @@
+//! Bridge from the [`log`](https://docs.rs/log) facade into polyfmt.
+//!
+//! Libraries that log through the `log` crate would otherwise write straight past polyfmt, corrupting spinner
+//! output or breaking a JSON stream. Installing this logger routes every record through the global formatter
+//! instead, so log lines look like any other polyfmt output.
+//!
+//! Records map onto formatter calls by level:
+//!
+//! * `Error` → [`error`](crate::Formatter::error)
+//! * `Warn` → [`warning`](crate::Formatter::warning)
+//! * `Info` → [`println`](crate::Formatter::println)
+//! * `Debug` → [`debug`](crate::Formatter::debug)
+//! * `Trace` → [`trace`](crate::Formatter::trace)
+//!
+//! The record's target and module path are attached as [metadata](crate::Formatter::with_metadata), so they show
+//! up in [`Json`](crate::Format::Json) records but not in human readable output.
+//!
+//! This module is only available with the `log` feature.
+
+use crate::get_global_formatter;
+use ::log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+/// Installs polyfmt as the logger for the `log` facade, forwarding all records up to `max_level` to the global
+/// formatter.
+///
+/// Since the `log` facade only allows a single logger this fails if a logger has already been installed.
+///
+/// # Example
+///
+/// ```
+/// polyfmt::log::init(log::LevelFilter::Info).unwrap();
+/// log::info!("hello from the log crate");
+/// ```
+pub fn init(max_level: LevelFilter) -> Result<(), SetLoggerError> {
+    ::log::set_boxed_logger(Box::new(Logger))?;
+    ::log::set_max_level(max_level);
+    Ok(())
+}
+
+struct Logger;
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= ::log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let message = record.args().to_string();
+
+        // Logging from inside a panicking print shouldn't take the rest of the program down with it.
+        let global_fmtter = get_global_formatter();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let module_path = record.module_path().unwrap_or_default();
+        fmt.with_metadata(&[("target", &record.target()), ("module_path", &module_path)]);
+
+        match record.level() {
+            Level::Error => fmt.error(&message),
+            Level::Warn => fmt.warning(&message),
+            Level::Info => fmt.println(&message),
+            Level::Debug => fmt.debug(&message),
+            Level::Trace => fmt.trace(&message),
+        }
+    }
+
+    fn flush(&self) {}
+}
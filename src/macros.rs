@@ -8,14 +8,17 @@
 /// print!("Hello, {name}");
 /// print!("Hello Clint");
 /// print!("Hello, {}", name);
-/// print!("Hello, {}", name; vec![Format::Plain])
+/// print!("Hello, {}", name; vec![Format::Plain]);
+/// print!("Hello, {}", name; id = 42, region = "us")
 /// ```
 #[macro_export]
 macro_rules! print {
     // Simply prints a newline when nothing else is given.
     () => ({
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.print("");
     });
 
@@ -23,7 +26,9 @@ macro_rules! print {
     // e.g: print!("Hello, {}", Clint) and print!("Hello, {clint}")
     ($s:expr $(, $arg:expr),*) => ({
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.print(&format!("{}", format_args!($s, $($arg),*)));
     });
 
@@ -31,17 +36,33 @@ macro_rules! print {
     // e.g: print!("Hello, {}, {}", Clint, "How are you")
     ($s:expr, $($arg:expr),*) => ({
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.print(&format!("{}", format_args!($s, $($arg),*)));
     });
 
+    // Allows attaching structured fields to the message. This has to come before the filter
+    // arm since `key = value` also parses as an expression.
+    // e.g: print!("failed to fetch"; id = 42, region = "us")
+    ($s:expr $(, $args:expr)* ; $($key:ident = $value:expr),+ $(,)?) => {{
+        let global_fmtter = $crate::get_global_formatter();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
+        fmt.with_fields(&[$((stringify!($key), &$value as &dyn $crate::Displayable)),+])
+            .print(&format!("{}", format_args!($s, $($args),*)));
+    }};
+
     // Allows a simple format style string with some arguments or none and also
     // accounts for if the user wants to insert a formatter filter.
     // e.g: print!("Hello, {}", Clint; vec![Format::Plain])
     // e.g: print!("Hello, {}", Clint; vec![Format::Plain])
     ($s:expr $(, $args:expr)* ; $formats:expr) => {{
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.only($formats).print(&format!("{}", format_args!($s, $($args),*)));
     }};
 }
@@ -56,14 +77,17 @@ macro_rules! print {
 /// println!("Hello, {name}");
 /// println!("Hello Clint");
 /// println!("Hello, {}", name);
-/// println!("Hello, {}", name; vec![Format::Plain])
+/// println!("Hello, {}", name; vec![Format::Plain]);
+/// println!("Hello, {}", name; id = 42, region = "us")
 /// ```
 #[macro_export]
 macro_rules! println {
     // Simply prints a newline when nothing else is given.
     () => ({
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.println(&"\n");
     });
 
@@ -71,7 +95,9 @@ macro_rules! println {
     // e.g: print!("Hello, {}", Clint) and print!("Hello, {clint}")
     ($s:expr $(, $arg:expr),*) => ({
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.println(&format!("{}", format_args!($s, $($arg),*)));
     });
 
@@ -79,16 +105,32 @@ macro_rules! println {
     // e.g: print!("Hello, {}, {}", Clint, "How are you")
     ($s:expr, $($arg:expr),*) => ({
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.println(&format!("{}", format_args!($s, $($arg),*)));
     });
 
+    // Allows attaching structured fields to the message. This has to come before the filter
+    // arm since `key = value` also parses as an expression.
+    // e.g: println!("failed to fetch"; id = 42, region = "us")
+    ($s:expr $(, $args:expr)* ; $($key:ident = $value:expr),+ $(,)?) => {{
+        let global_fmtter = $crate::get_global_formatter();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
+        fmt.with_fields(&[$((stringify!($key), &$value as &dyn $crate::Displayable)),+])
+            .println(&format!("{}", format_args!($s, $($args),*)));
+    }};
+
     // Allows a simple format style string with some arguments or none and also
     // accounts for if the user wants to insert a formatter filter.
     // e.g: print!("Hello, {}", Clint; vec![Format::Plain])
     ($s:expr $(, $args:expr)* ; $formats:expr) => {{
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.only($formats).println(&format!("{}", format_args!($s, $($args),*)));
     }};
 }
@@ -103,7 +145,8 @@ macro_rules! println {
 /// success!("Hello, {name}");
 /// success!("Hello Clint");
 /// success!("Hello, {}", name);
-/// success!("Hello, {}", name; vec![Format::Plain])
+/// success!("Hello, {}", name; vec![Format::Plain]);
+/// success!("Hello, {}", name; id = 42, region = "us")
 /// ```
 #[macro_export]
 macro_rules! success {
@@ -111,7 +154,9 @@ macro_rules! success {
     // e.g: print!("Hello, {}", Clint) and print!("Hello, {clint}")
     ($s:expr $(, $arg:expr),*) => ({
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.success(&format!("{}", format_args!($s, $($arg),*)));
     });
 
@@ -119,16 +164,32 @@ macro_rules! success {
     // e.g: print!("Hello, {}, {}", Clint, "How are you")
     ($s:expr, $($arg:expr),*) => ({
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.success(&format!("{}", format_args!($s, $($arg),*)));
     });
 
+    // Allows attaching structured fields to the message. This has to come before the filter
+    // arm since `key = value` also parses as an expression.
+    // e.g: success!("failed to fetch"; id = 42, region = "us")
+    ($s:expr $(, $args:expr)* ; $($key:ident = $value:expr),+ $(,)?) => {{
+        let global_fmtter = $crate::get_global_formatter();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
+        fmt.with_fields(&[$((stringify!($key), &$value as &dyn $crate::Displayable)),+])
+            .success(&format!("{}", format_args!($s, $($args),*)));
+    }};
+
     // Allows a simple format style string with some arguments or none and also
     // accounts for if the user wants to insert a formatter filter.
     // e.g: print!("Hello, {}", Clint; vec![Format::Plain])
     ($s:expr $(, $args:expr)* ; $formats:expr) => {{
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.only($formats).success(&format!("{}", format_args!($s, $($args),*)));
     }};
 }
@@ -143,7 +204,8 @@ macro_rules! success {
 /// error!("Hello, {name}");
 /// error!("Hello Clint");
 /// error!("Hello, {}", name);
-/// error!("Hello, {}", name; vec![Format::Plain])
+/// error!("Hello, {}", name; vec![Format::Plain]);
+/// error!("Hello, {}", name; id = 42, region = "us")
 /// ```
 #[macro_export]
 macro_rules! error {
@@ -151,7 +213,9 @@ macro_rules! error {
     // e.g: print!("Hello, {}", Clint) and print!("Hello, {clint}")
     ($s:expr $(, $arg:expr),*) => ({
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.error(&format!("{}", format_args!($s, $($arg),*)));
     });
 
@@ -159,16 +223,32 @@ macro_rules! error {
     // e.g: print!("Hello, {}, {}", Clint, "How are you")
     ($s:expr, $($arg:expr),*) => ({
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.error(&format!("{}", format_args!($s, $($arg),*)));
     });
 
+    // Allows attaching structured fields to the message. This has to come before the filter
+    // arm since `key = value` also parses as an expression.
+    // e.g: error!("failed to fetch"; id = 42, region = "us")
+    ($s:expr $(, $args:expr)* ; $($key:ident = $value:expr),+ $(,)?) => {{
+        let global_fmtter = $crate::get_global_formatter();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
+        fmt.with_fields(&[$((stringify!($key), &$value as &dyn $crate::Displayable)),+])
+            .error(&format!("{}", format_args!($s, $($args),*)));
+    }};
+
     // Allows a simple format style string with some arguments or none and also
     // accounts for if the user wants to insert a formatter filter.
     // e.g: print!("Hello, {}", Clint; vec![Format::Plain])
     ($s:expr $(, $args:expr)* ; $formats:expr) => {{
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.only($formats).error(&format!("{}", format_args!($s, $($args),*)));
     }};
 }
@@ -190,7 +270,9 @@ macro_rules! error {
 macro_rules! indent {
     () => {{
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.indent()
     }};
 }
@@ -207,11 +289,47 @@ macro_rules! indent {
 macro_rules! spacer {
     () => {{
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.spacer();
     }};
 }
 
+/// Print rows of data as a table, determined by the [`Formatter`].
+///
+/// # Examples
+///
+/// ```
+/// # use polyfmt::{table, Format};
+/// let rows = vec![
+///     vec!["api".to_string(), "running".to_string()],
+///     vec!["worker".to_string(), "stopped".to_string()],
+/// ];
+/// table!(&["NAME", "STATUS"], &rows);
+/// table!(&["NAME", "STATUS"], &rows; vec![Format::Plain]);
+/// ```
+#[macro_export]
+macro_rules! table {
+    ($headers:expr, $rows:expr) => {{
+        let global_fmtter = $crate::get_global_formatter();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
+        fmt.table($headers, $rows);
+    }};
+
+    // Accounts for if the user wants to insert a formatter filter.
+    // e.g: table!(&["NAME"], &rows; vec![Format::Plain])
+    ($headers:expr, $rows:expr; $formats:expr) => {{
+        let global_fmtter = $crate::get_global_formatter();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
+        fmt.only($formats).table($headers, $rows);
+    }};
+}
+
 /// Temporarily pauses dynamic or animated output.
 ///
 /// This is primarily used by formatters that render animated elements such as
@@ -226,7 +344,9 @@ macro_rules! spacer {
 macro_rules! pause {
     () => {{
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.pause();
     }};
 }
@@ -242,16 +362,22 @@ macro_rules! pause {
 macro_rules! resume {
     () => {{
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.resume();
     }};
 }
 
+/// Flushes any buffered output of the global formatter and cleans up anything else it needs to before the program
+/// exits. Since the global formatter is never dropped, programs using it should call this before they exit.
 #[macro_export]
 macro_rules! finish {
     () => {{
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.finish();
     }};
 }
@@ -266,7 +392,8 @@ macro_rules! finish {
 /// warning!("Hello, {name}");
 /// warning!("Hello Clint");
 /// warning!("Hello, {}", name);
-/// warning!("Hello, {}", name; vec![Format::Plain])
+/// warning!("Hello, {}", name; vec![Format::Plain]);
+/// warning!("Hello, {}", name; id = 42, region = "us")
 /// ```
 #[macro_export]
 macro_rules! warning {
@@ -274,7 +401,9 @@ macro_rules! warning {
     // e.g: print!("Hello, {}", Clint) and print!("Hello, {clint}")
     ($s:expr $(, $arg:expr),*) => ({
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.warning(&format!("{}", format_args!($s, $($arg),*)));
     });
 
@@ -282,16 +411,32 @@ macro_rules! warning {
     // e.g: print!("Hello, {}, {}", Clint, "How are you")
     ($s:expr, $($arg:expr),*) => ({
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.warning(&format!("{}", format_args!($s, $($arg),*)));
     });
 
+    // Allows attaching structured fields to the message. This has to come before the filter
+    // arm since `key = value` also parses as an expression.
+    // e.g: warning!("failed to fetch"; id = 42, region = "us")
+    ($s:expr $(, $args:expr)* ; $($key:ident = $value:expr),+ $(,)?) => {{
+        let global_fmtter = $crate::get_global_formatter();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
+        fmt.with_fields(&[$((stringify!($key), &$value as &dyn $crate::Displayable)),+])
+            .warning(&format!("{}", format_args!($s, $($args),*)));
+    }};
+
     // Allows a simple format style string with some arguments or none and also
     // accounts for if the user wants to insert a formatter filter.
     // e.g: print!("Hello, {}", Clint; vec![Format::Plain])
     ($s:expr $(, $args:expr)* ; $formats:expr) => {{
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.only($formats).warning(&format!("{}", format_args!($s, $($args),*)));
     }};
 }
@@ -306,6 +451,7 @@ macro_rules! warning {
 /// question!("Hello, {name}");
 /// question!("Hello Clint");
 /// question!("Hello, {}", name; vec![Format::Plain]);
+/// question!("Hello, {}", name; id = 42);
 /// let input = question!("Hello, {}", name);
 /// ```
 #[macro_export]
@@ -314,7 +460,9 @@ macro_rules! question {
     // e.g: print!("Hello, {}", Clint) and print!("Hello, {clint}")
     ($s:expr $(, $arg:expr),*) => ({
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.question(&format!("{}", format_args!($s, $($arg),*)))
     });
 
@@ -322,16 +470,32 @@ macro_rules! question {
     // e.g: print!("Hello, {}, {}", Clint, "How are you")
     ($s:expr, $($arg:expr),*) => ({
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.question(&format!("{}", format_args!($s, $($arg),*)))
     });
 
+    // Allows attaching structured fields to the message. This has to come before the filter
+    // arm since `key = value` also parses as an expression.
+    // e.g: question!("failed to fetch"; id = 42, region = "us")
+    ($s:expr $(, $args:expr)* ; $($key:ident = $value:expr),+ $(,)?) => {{
+        let global_fmtter = $crate::get_global_formatter();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
+        fmt.with_fields(&[$((stringify!($key), &$value as &dyn $crate::Displayable)),+])
+            .question(&format!("{}", format_args!($s, $($args),*)))
+    }};
+
     // Allows a simple format style string with some arguments or none and also
     // accounts for if the user wants to insert a formatter filter.
     // e.g: print!("Hello, {}", Clint; vec![Format::Plain])
     ($s:expr $(, $args:expr)* ; $formats:expr) => {{
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.only($formats).question(&format!("{}", format_args!($s, $($args),*)))
     }};
 }
@@ -346,7 +510,8 @@ macro_rules! question {
 /// debug!("Hello, {name}");
 /// debug!("Hello Clint");
 /// debug!("Hello, {}", name);
-/// debug!("Hello, {}", name; vec![Format::Plain])
+/// debug!("Hello, {}", name; vec![Format::Plain]);
+/// debug!("Hello, {}", name; id = 42, region = "us")
 /// ```
 #[macro_export]
 macro_rules! debug {
@@ -354,7 +519,9 @@ macro_rules! debug {
     // e.g: print!("Hello, {}", Clint) and print!("Hello, {clint}")
     ($s:expr $(, $arg:expr),*) => ({
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.debug(&format!("{}", format_args!($s, $($arg),*)));
     });
 
@@ -362,16 +529,91 @@ macro_rules! debug {
     // e.g: print!("Hello, {}, {}", Clint, "How are you")
     ($s:expr, $($arg:expr),*) => ({
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.debug(&format!("{}", format_args!($s, $($arg),*)));
     });
 
+    // Allows attaching structured fields to the message. This has to come before the filter
+    // arm since `key = value` also parses as an expression.
+    // e.g: debug!("failed to fetch"; id = 42, region = "us")
+    ($s:expr $(, $args:expr)* ; $($key:ident = $value:expr),+ $(,)?) => {{
+        let global_fmtter = $crate::get_global_formatter();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
+        fmt.with_fields(&[$((stringify!($key), &$value as &dyn $crate::Displayable)),+])
+            .debug(&format!("{}", format_args!($s, $($args),*)));
+    }};
+
     // Allows a simple format style string with some arguments or none and also
     // accounts for if the user wants to insert a formatter filter.
     // e.g: print!("Hello, {}", Clint; vec![Format::Plain])
     ($s:expr $(, $args:expr)* ; $formats:expr) => {{
         let global_fmtter = $crate::get_global_formatter();
-        let mut fmt = global_fmtter.lock().unwrap();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
         fmt.only($formats).debug(&format!("{}", format_args!($s, $($args),*)));
     }};
 }
+
+/// Print a trace message that only shows up if the verbosity is set to trace.
+///
+/// # Examples
+///
+/// ```
+/// # use polyfmt::{trace, Format};
+/// let name = "Clint";
+/// trace!("Hello, {name}");
+/// trace!("Hello Clint");
+/// trace!("Hello, {}", name);
+/// trace!("Hello, {}", name; vec![Format::Plain]);
+/// trace!("Hello, {}", name; id = 42, region = "us")
+/// ```
+#[macro_export]
+macro_rules! trace {
+    // Allows a simple format style string, with one arguments or none.
+    // e.g: print!("Hello, {}", Clint) and print!("Hello, {clint}")
+    ($s:expr $(, $arg:expr),*) => ({
+        let global_fmtter = $crate::get_global_formatter();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
+        fmt.trace(&format!("{}", format_args!($s, $($arg),*)));
+    });
+
+    // Allows a simple format style string, with many arguments or none.
+    // e.g: print!("Hello, {}, {}", Clint, "How are you")
+    ($s:expr, $($arg:expr),*) => ({
+        let global_fmtter = $crate::get_global_formatter();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
+        fmt.trace(&format!("{}", format_args!($s, $($arg),*)));
+    });
+
+    // Allows attaching structured fields to the message. This has to come before the filter
+    // arm since `key = value` also parses as an expression.
+    // e.g: trace!("failed to fetch"; id = 42, region = "us")
+    ($s:expr $(, $args:expr)* ; $($key:ident = $value:expr),+ $(,)?) => {{
+        let global_fmtter = $crate::get_global_formatter();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
+        fmt.with_fields(&[$((stringify!($key), &$value as &dyn $crate::Displayable)),+])
+            .trace(&format!("{}", format_args!($s, $($args),*)));
+    }};
+
+    // Allows a simple format style string with some arguments or none and also
+    // accounts for if the user wants to insert a formatter filter.
+    // e.g: print!("Hello, {}", Clint; vec![Format::Plain])
+    ($s:expr $(, $args:expr)* ; $formats:expr) => {{
+        let global_fmtter = $crate::get_global_formatter();
+        let mut fmt = global_fmtter
+            .lock()
+            .unwrap_or_else(::std::sync::PoisonError::into_inner);
+        fmt.only($formats).trace(&format!("{}", format_args!($s, $($args),*)));
+    }};
+}
@@ -1,28 +1,59 @@
 use crate::{
-    format_text_by_length, take_and_check_allowed, Displayable, Format, Formatter, IndentGuard,
-    Options,
+    ansi, format_fields, format_table_columns, format_text_with_fields, take_and_check_allowed,
+    Destination, Displayable, Field, Format, Formatter, IndentGuard, Options, ProgressHandle,
+    Routing, TaskHandle, Theme, Verbosity, WriteErrors,
+};
+use crate::drain::Drain;
+use crate::link::Hyperlinks;
+use crate::progress::{LineKind, LinePrinter, LineProgress, NoopProgress};
+use crate::task::{LineTask, NoopTask};
+use crate::timestamp::{Clock, Stamped};
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+use std::{
+    collections::HashSet,
+    io::{self, Write},
 };
-use colored::Colorize;
-use std::sync::{Arc, Mutex, Weak};
-use std::{collections::HashSet, io::Write};
 
-#[derive(Clone)]
 pub struct Plain {
-    debug: bool,
+    verbosity: Verbosity,
     indentation_level: u16,
     max_line_length: usize,
     allowed_formats: HashSet<Format>,
+    fields: Vec<Field>,
     output_target: Arc<Mutex<dyn Write + Send>>,
+    diagnostic_target: Arc<Mutex<dyn Write + Send>>,
+    routing: Routing,
+    write_errors: WriteErrors,
+    theme: Theme,
+    hyperlinks: Hyperlinks,
 }
 
 impl Plain {
     pub fn new(options: Options) -> Arc<Mutex<Self>> {
+        let hyperlinks = Hyperlinks::detect(&options.output_target, &options.diagnostic_target);
+        let clock = Clock::new(options.timestamps);
+
         Arc::new(Mutex::new(Plain {
-            debug: options.debug,
+            verbosity: options.verbosity,
             indentation_level: 0,
             allowed_formats: HashSet::new(),
-            max_line_length: options.max_line_length,
-            output_target: options.output_target.target,
+            fields: Vec::new(),
+            max_line_length: options.max_line_length.saturating_sub(clock.width()),
+            output_target: Stamped::wrap(
+                Drain::wrap(options.output_target.target, options.exit_on_broken_pipe),
+                clock,
+            ),
+            diagnostic_target: Stamped::wrap(
+                Drain::wrap(
+                    options.diagnostic_target.target,
+                    options.exit_on_broken_pipe,
+                ),
+                clock,
+            ),
+            routing: options.routing,
+            write_errors: WriteErrors::new(options.write_error_policy),
+            theme: options.theme,
+            hyperlinks,
         }))
     }
 }
@@ -51,172 +82,234 @@ impl Drop for Guard {
 }
 
 impl Plain {
-    fn print(&mut self, msg: &dyn Displayable) {
-        if !take_and_check_allowed(Format::Plain, &mut self.allowed_formats) {
-            return;
+    /// Returns the target for messages routed to the given destination.
+    fn target(&self, destination: Destination) -> &Arc<Mutex<dyn Write + Send>> {
+        match destination {
+            Destination::Output => &self.output_target,
+            Destination::Diagnostic => &self.diagnostic_target,
+        }
+    }
+
+    fn print(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let fields = std::mem::take(&mut self.fields);
+        if !take_and_check_allowed(Format::Plain, &mut self.allowed_formats)
+            || !self.verbosity.allows(Verbosity::Info)
+        {
+            return Ok(());
         }
 
+        let msg = self.hyperlinks.render(Destination::Output, msg);
         let mut output_target = self.output_target.lock().unwrap();
-        let _ = write!(output_target, "{msg}");
+        write!(output_target, "{msg}{}", format_fields(&fields))?;
+
+        Ok(())
     }
 
-    fn println(&mut self, msg: &dyn Displayable) {
-        if !take_and_check_allowed(Format::Plain, &mut self.allowed_formats) {
-            return;
+    fn println(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let fields = std::mem::take(&mut self.fields);
+        if !take_and_check_allowed(Format::Plain, &mut self.allowed_formats)
+            || !self.verbosity.allows(Verbosity::Info)
+        {
+            return Ok(());
         }
 
-        let lines = format_text_by_length(msg, self.indentation_level, self.max_line_length);
+        let lines = format_text_with_fields(
+            &self.hyperlinks.render(Destination::Output, msg),
+            &fields,
+            self.indentation_level,
+            self.max_line_length,
+        );
 
         if lines.is_empty() {
-            return;
+            return Ok(());
         }
 
         let mut output_target = self.output_target.lock().unwrap();
 
-        let _ = writeln!(
+        writeln!(
             output_target,
             "{}{}",
             " ".repeat(self.indentation_level.into()),
             lines.first().unwrap_or(&"".to_string()),
-        );
+        )?;
 
         // Print the remaining lines
         for line in lines.iter().skip(1) {
-            let _ = writeln!(
+            writeln!(
                 output_target,
                 "{}{}",
                 " ".repeat(self.indentation_level.into()),
                 line
-            );
+            )?;
         }
+
+        Ok(())
     }
 
-    fn error(&mut self, msg: &dyn Displayable) {
+    fn error(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let fields = std::mem::take(&mut self.fields);
         if !take_and_check_allowed(Format::Plain, &mut self.allowed_formats) {
-            return;
+            return Ok(());
         }
 
-        let lines = format_text_by_length(msg, self.indentation_level + 2, self.max_line_length);
+        let glyph = &self.theme.error;
+        let offset = self.indentation_level + glyph.width() + 1;
+        let lines = format_text_with_fields(
+            &self.hyperlinks.render(self.routing.error, msg),
+            &fields,
+            offset,
+            self.max_line_length,
+        );
 
         if lines.is_empty() {
-            return;
+            return Ok(());
         }
 
-        let mut output_target = self.output_target.lock().unwrap();
+        let mut output_target = self.target(self.routing.error).lock().unwrap();
 
-        let _ = writeln!(
+        writeln!(
             output_target,
             "{}{} {}",
             " ".repeat(self.indentation_level.into()),
-            "x".red(),
+            glyph,
             lines.first().unwrap_or(&"".to_string()),
-        );
+        )?;
 
         // Print the remaining lines
         for line in lines.iter().skip(1) {
-            let _ = writeln!(
-                output_target,
-                "{}{}",
-                " ".repeat((self.indentation_level + 2).into()),
-                line
-            );
+            writeln!(output_target, "{}{}", " ".repeat(offset.into()), line)?;
         }
+
+        Ok(())
     }
 
-    fn success(&mut self, msg: &dyn Displayable) {
-        if !take_and_check_allowed(Format::Plain, &mut self.allowed_formats) {
-            return;
+    fn success(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let fields = std::mem::take(&mut self.fields);
+        if !take_and_check_allowed(Format::Plain, &mut self.allowed_formats)
+            || !self.verbosity.allows(Verbosity::Info)
+        {
+            return Ok(());
         }
 
-        let lines = format_text_by_length(msg, self.indentation_level + 2, self.max_line_length);
+        let glyph = &self.theme.success;
+        let offset = self.indentation_level + glyph.width() + 1;
+        let lines = format_text_with_fields(
+            &self.hyperlinks.render(self.routing.success, msg),
+            &fields,
+            offset,
+            self.max_line_length,
+        );
 
         if lines.is_empty() {
-            return;
+            return Ok(());
         }
 
-        let mut output_target = self.output_target.lock().unwrap();
+        let mut output_target = self.target(self.routing.success).lock().unwrap();
 
-        let _ = writeln!(
+        writeln!(
             output_target,
             "{}{} {}",
             " ".repeat(self.indentation_level.into()),
-            "✓".green(),
+            glyph,
             lines.first().unwrap_or(&"".to_string()),
-        );
+        )?;
 
         // Print the remaining lines
         for line in lines.iter().skip(1) {
-            let _ = writeln!(
-                output_target,
-                "{}{}",
-                " ".repeat((self.indentation_level + 2).into()),
-                line
-            );
+            writeln!(output_target, "{}{}", " ".repeat(offset.into()), line)?;
         }
+
+        Ok(())
     }
 
-    fn warning(&mut self, msg: &dyn Displayable) {
-        if !take_and_check_allowed(Format::Plain, &mut self.allowed_formats) {
-            return;
+    fn warning(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let fields = std::mem::take(&mut self.fields);
+        if !take_and_check_allowed(Format::Plain, &mut self.allowed_formats)
+            || !self.verbosity.allows(Verbosity::Warn)
+        {
+            return Ok(());
         }
 
-        let lines = format_text_by_length(msg, self.indentation_level + 3, self.max_line_length);
+        let glyph = &self.theme.warning;
+        let offset = self.indentation_level + glyph.width() + 1;
+        let lines = format_text_with_fields(
+            &self.hyperlinks.render(self.routing.warning, msg),
+            &fields,
+            offset,
+            self.max_line_length,
+        );
 
         if lines.is_empty() {
-            return;
+            return Ok(());
         }
 
-        let mut output_target = self.output_target.lock().unwrap();
+        let mut output_target = self.target(self.routing.warning).lock().unwrap();
 
-        let _ = writeln!(
+        writeln!(
             output_target,
             "{}{} {}",
             " ".repeat(self.indentation_level.into()),
-            "!!".yellow(),
+            glyph,
             lines.first().unwrap_or(&"".to_string()),
-        );
+        )?;
 
         // Print the remaining lines
         for line in lines.iter().skip(1) {
-            let _ = writeln!(
-                output_target,
-                " {}{}",
-                " ".repeat((self.indentation_level + 2).into()),
-                line
-            );
+            writeln!(output_target, "{}{}", " ".repeat(offset.into()), line)?;
         }
+
+        Ok(())
     }
 
-    fn debug(&mut self, msg: &dyn Displayable) {
-        if !take_and_check_allowed(Format::Plain, &mut self.allowed_formats) || !self.debug {
-            return;
+    fn debug(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        self.leveled(Verbosity::Debug, msg)
+    }
+
+    fn trace(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        self.leveled(Verbosity::Trace, msg)
+    }
+
+    /// Prints debug and trace lines, which only differ in their glyph and the verbosity they need.
+    fn leveled(&mut self, level: Verbosity, msg: &dyn Displayable) -> io::Result<()> {
+        let fields = std::mem::take(&mut self.fields);
+        if !take_and_check_allowed(Format::Plain, &mut self.allowed_formats)
+            || !self.verbosity.allows(level)
+        {
+            return Ok(());
         }
 
-        let lines = format_text_by_length(msg, self.indentation_level + 8, self.max_line_length);
+        let glyph = match level {
+            Verbosity::Trace => &self.theme.trace,
+            _ => &self.theme.debug,
+        };
+        let offset = self.indentation_level + glyph.width() + 1;
+        let lines = format_text_with_fields(
+            &self.hyperlinks.render(self.routing.debug, msg),
+            &fields,
+            offset,
+            self.max_line_length,
+        );
 
         if lines.is_empty() {
-            return;
+            return Ok(());
         }
 
-        let mut output_target = self.output_target.lock().unwrap();
+        let mut output_target = self.target(self.routing.debug).lock().unwrap();
 
-        let _ = writeln!(
+        writeln!(
             output_target,
             "{}{} {}",
             " ".repeat(self.indentation_level.into()),
-            "[debug]".dimmed(),
+            glyph,
             lines.first().unwrap_or(&"".to_string()),
-        );
+        )?;
 
         // Print the remaining lines
         for line in lines.iter().skip(1) {
-            let _ = writeln!(
-                output_target,
-                "{} {}",
-                " ".repeat((self.indentation_level + 7).into()),
-                line
-            );
+            writeln!(output_target, "{}{}", " ".repeat(offset.into()), line)?;
         }
+
+        Ok(())
     }
 
     fn indent(fmtter: &Arc<Mutex<Self>>) -> Box<dyn IndentGuard> {
@@ -228,16 +321,86 @@ impl Plain {
         Box::new(guard)
     }
 
+    fn progress(
+        fmtter: &Arc<Mutex<Self>>,
+        total: u64,
+        msg: &dyn Displayable,
+    ) -> Box<dyn ProgressHandle> {
+        let mut fmt = fmtter.lock().unwrap();
+        fmt.fields.clear();
+        if !take_and_check_allowed(Format::Plain, &mut fmt.allowed_formats)
+            || !fmt.verbosity.allows(Verbosity::Info)
+        {
+            return Box::new(NoopProgress);
+        }
+        drop(fmt);
+
+        Box::new(LineProgress::new(Box::new(Arc::clone(fmtter)), total, msg))
+    }
+
+    fn task(fmtter: &Arc<Mutex<Self>>, name: &dyn Displayable) -> Box<dyn TaskHandle> {
+        let mut fmt = fmtter.lock().unwrap();
+        fmt.fields.clear();
+        if !take_and_check_allowed(Format::Plain, &mut fmt.allowed_formats)
+            || !fmt.verbosity.allows(Verbosity::Info)
+        {
+            return Box::new(NoopTask);
+        }
+        drop(fmt);
+
+        Box::new(LineTask::new(Box::new(Arc::clone(fmtter)), name))
+    }
+
     fn outdent(&mut self) {
         if self.indentation_level > 0 {
             self.indentation_level -= 1;
         }
     }
 
-    fn spacer(&mut self) {
+    fn spacer(&mut self) -> io::Result<()> {
+        if !self.verbosity.allows(Verbosity::Info) {
+            return Ok(());
+        }
+
         let mut output_target = self.output_target.lock().unwrap();
 
-        let _ = writeln!(output_target);
+        writeln!(output_target)?;
+
+        Ok(())
+    }
+
+    fn table(&mut self, headers: &[&str], rows: &[Vec<String>]) -> io::Result<()> {
+        self.fields.clear();
+        if !take_and_check_allowed(Format::Plain, &mut self.allowed_formats)
+            || !self.verbosity.allows(Verbosity::Info)
+        {
+            return Ok(());
+        }
+
+        let max_width = self
+            .max_line_length
+            .saturating_sub(self.indentation_level.into());
+        let (header, body) = format_table_columns(headers, rows, max_width, &self.theme.ellipsis);
+
+        let mut output_target = self.output_target.lock().unwrap();
+
+        writeln!(
+            output_target,
+            "{}{}",
+            " ".repeat(self.indentation_level.into()),
+            ansi::bold(&header)
+        )?;
+
+        for line in body {
+            writeln!(
+                output_target,
+                "{}{}",
+                " ".repeat(self.indentation_level.into()),
+                line
+            )?;
+        }
+
+        Ok(())
     }
 
     #[allow(dead_code)]
@@ -246,63 +409,64 @@ impl Plain {
     #[allow(dead_code)]
     fn start(&mut self) {}
 
-    fn question(&mut self, msg: &dyn Displayable) -> String {
+    fn question(&mut self, msg: &dyn Displayable) -> io::Result<String> {
+        let fields = std::mem::take(&mut self.fields);
         if !take_and_check_allowed(Format::Plain, &mut self.allowed_formats) {
-            return "".to_string();
+            return Ok("".to_string());
         }
 
-        let lines = format_text_by_length(msg, self.indentation_level + 2, self.max_line_length);
+        let glyph = &self.theme.question;
+        let offset = self.indentation_level + glyph.width() + 1;
+        let lines = format_text_with_fields(
+            &self.hyperlinks.render(self.routing.question, msg),
+            &fields,
+            offset,
+            self.max_line_length,
+        );
 
-        let mut output_target = self.output_target.lock().unwrap();
+        let mut output_target = self
+            .target(self.routing.question)
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
 
         if lines.len() == 1 {
-            let _ = write!(
+            write!(
                 output_target,
                 "{}{} {}",
                 " ".repeat(self.indentation_level.into()),
-                "?".magenta(),
+                glyph,
                 lines.first().unwrap_or(&"".to_string()),
-            );
+            )?;
         } else {
-            let _ = writeln!(
+            writeln!(
                 output_target,
                 "{}{} {}",
                 " ".repeat(self.indentation_level.into()),
-                "?".magenta(),
+                glyph,
                 lines.first().unwrap_or(&"".to_string()),
-            );
+            )?;
 
             // Print the remaining lines except the last with writeln!
             let lines_count = lines.len();
             for (index, line) in lines.iter().enumerate().skip(1) {
                 if index + 1 < lines_count {
                     // Not the last line
-                    let _ = writeln!(
-                        output_target,
-                        "{}{}",
-                        " ".repeat((self.indentation_level + 2).into()),
-                        line
-                    );
+                    writeln!(output_target, "{}{}", " ".repeat(offset.into()), line)?;
                 } else {
                     // Last line, use print! instead
-                    let _ = write!(
-                        output_target,
-                        "{}{}",
-                        " ".repeat((self.indentation_level + 2).into()),
-                        line
-                    );
+                    write!(output_target, "{}{}", " ".repeat(offset.into()), line)?;
                 }
             }
         }
 
-        output_target.flush().unwrap();
+        output_target.flush()?;
         drop(output_target);
 
         let mut input = String::from("");
 
-        let _ = std::io::stdin().read_line(&mut input);
+        std::io::stdin().read_line(&mut input)?;
 
-        input.trim().to_string()
+        Ok(input.trim().to_string())
     }
 
     fn only(&mut self, types: Vec<Format>) -> &mut Self {
@@ -310,48 +474,98 @@ impl Plain {
         self
     }
 
-    fn finish(&self) {
-        if let Ok(mut out) = self.output_target.lock() {
-            let _ = out.flush();
+    fn with_fields(&mut self, fields: &[(&str, &dyn Displayable)]) -> &mut Self {
+        self.fields = fields
+            .iter()
+            .map(|(key, value)| Field::new(key, *value))
+            .collect();
+        self
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        for target in [&self.output_target, &self.diagnostic_target] {
+            if let Ok(mut out) = target.lock() {
+                out.flush()?;
+            }
         }
+
+        Ok(())
+    }
+}
+
+impl LinePrinter for Arc<Mutex<Plain>> {
+    fn print_line(&self, kind: LineKind, line: &str) {
+        let mut fmt = self.lock().unwrap();
+        let fields = std::mem::take(&mut fmt.fields);
+        let allowed_formats = std::mem::take(&mut fmt.allowed_formats);
+
+        let result = match kind {
+            LineKind::Info => fmt.println(&line),
+            LineKind::Success => fmt.success(&line),
+            LineKind::Error => fmt.error(&line),
+        };
+        fmt.write_errors.handle(result);
+
+        fmt.fields = fields;
+        fmt.allowed_formats = allowed_formats;
     }
 }
 
 impl Formatter for Arc<Mutex<Plain>> {
     fn print(&mut self, msg: &dyn Displayable) {
         let mut fmt = self.lock().unwrap();
-        fmt.print(msg);
+        let result = fmt.print(msg);
+        fmt.write_errors.handle(result);
     }
 
     fn println(&mut self, msg: &dyn Displayable) {
         let mut fmt = self.lock().unwrap();
-        fmt.println(msg);
+        let result = fmt.println(msg);
+        fmt.write_errors.handle(result);
     }
 
     fn error(&mut self, msg: &dyn Displayable) {
         let mut fmt = self.lock().unwrap();
-        fmt.error(msg);
+        let result = fmt.error(msg);
+        fmt.write_errors.handle(result);
     }
 
     fn success(&mut self, msg: &dyn Displayable) {
         let mut fmt = self.lock().unwrap();
-        fmt.success(msg);
+        let result = fmt.success(msg);
+        fmt.write_errors.handle(result);
     }
 
     fn warning(&mut self, msg: &dyn Displayable) {
         let mut fmt = self.lock().unwrap();
-        fmt.warning(msg);
+        let result = fmt.warning(msg);
+        fmt.write_errors.handle(result);
     }
 
     fn debug(&mut self, msg: &dyn Displayable) {
         let mut fmt = self.lock().unwrap();
-        fmt.debug(msg);
+        let result = fmt.debug(msg);
+        fmt.write_errors.handle(result);
+    }
+
+    fn trace(&mut self, msg: &dyn Displayable) {
+        let mut fmt = self.lock().unwrap();
+        let result = fmt.trace(msg);
+        fmt.write_errors.handle(result);
     }
 
     fn indent(&mut self) -> Box<dyn IndentGuard> {
         Plain::indent(self)
     }
 
+    fn progress(&mut self, total: u64, msg: &dyn Displayable) -> Box<dyn ProgressHandle> {
+        Plain::progress(self, total, msg)
+    }
+
+    fn task(&mut self, name: &dyn Displayable) -> Box<dyn TaskHandle> {
+        Plain::task(self, name)
+    }
+
     fn outdent(&mut self) {
         let mut fmt = self.lock().unwrap();
         fmt.outdent();
@@ -359,7 +573,14 @@ impl Formatter for Arc<Mutex<Plain>> {
 
     fn spacer(&mut self) {
         let mut fmt = self.lock().unwrap();
-        fmt.spacer()
+        let result = fmt.spacer();
+        fmt.write_errors.handle(result);
+    }
+
+    fn table(&mut self, headers: &[&str], rows: &[Vec<String>]) {
+        let mut fmt = self.lock().unwrap();
+        let result = fmt.table(headers, rows);
+        fmt.write_errors.handle(result);
     }
 
     fn pause(&mut self) {}
@@ -368,7 +589,8 @@ impl Formatter for Arc<Mutex<Plain>> {
 
     fn question(&mut self, msg: &dyn Displayable) -> String {
         let mut fmt = self.lock().unwrap();
-        fmt.question(msg)
+        let result = fmt.question(msg);
+        fmt.write_errors.handle(result)
     }
 
     fn only(&mut self, types: Vec<Format>) -> &mut dyn Formatter {
@@ -378,8 +600,80 @@ impl Formatter for Arc<Mutex<Plain>> {
         self
     }
 
+    fn with_fields(&mut self, fields: &[(&str, &dyn Displayable)]) -> &mut dyn Formatter {
+        let mut fmt = self.lock().unwrap();
+        fmt.with_fields(fields);
+        drop(fmt);
+        self
+    }
+
+    fn with_metadata(&mut self, _fields: &[(&str, &dyn Displayable)]) -> &mut dyn Formatter {
+        self
+    }
+
+    fn format(&self) -> Format {
+        Format::Plain
+    }
+
     fn finish(&self) {
-        let fmt = self.lock().unwrap();
-        fmt.finish();
+        let mut fmt = self.lock().unwrap();
+        let result = fmt.finish();
+        fmt.write_errors.handle(result);
+    }
+
+    fn try_print(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.print(msg)
+    }
+
+    fn try_println(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.println(msg)
+    }
+
+    fn try_error(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.error(msg)
+    }
+
+    fn try_success(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.success(msg)
+    }
+
+    fn try_warning(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.warning(msg)
+    }
+
+    fn try_debug(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.debug(msg)
+    }
+
+    fn try_trace(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.trace(msg)
+    }
+
+    fn try_spacer(&mut self) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.spacer()
+    }
+
+    fn try_table(&mut self, headers: &[&str], rows: &[Vec<String>]) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.table(headers, rows)
+    }
+
+    fn try_question(&mut self, msg: &dyn Displayable) -> io::Result<String> {
+        let mut fmt = self.lock().unwrap();
+        fmt.question(msg)
+    }
+
+    fn try_finish(&self) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        let result = fmt.finish();
+        fmt.write_errors.take().and(result)
     }
 }
@@ -0,0 +1,149 @@
+use crate::{Displayable, ProgressHandle};
+use std::sync::Mutex;
+
+/// Kinds of lines printed by [`LineProgress`] and [`LineTask`](crate::task::LineTask).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LineKind {
+    Info,
+    Success,
+    Error,
+}
+
+/// Formatter the line based progress and task handles print through. Unlike the [`Formatter`](crate::Formatter)
+/// methods, printing a line leaves the fields and filters set up with
+/// [`with_fields`](crate::Formatter::with_fields) and [`only`](crate::Formatter::only) alone, so that they still apply
+/// to the next call of whoever set them.
+pub(crate) trait LinePrinter: Send {
+    fn print_line(&self, kind: LineKind, line: &str);
+}
+
+/// How far along a progress indicator is. Shared between the different progress handle implementations.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ProgressState {
+    pub total: u64,
+    pub position: u64,
+    pub message: String,
+    pub finished: bool,
+}
+
+impl ProgressState {
+    pub fn new(total: u64, msg: &dyn Displayable) -> Self {
+        Self {
+            total,
+            position: 0,
+            message: msg.to_string(),
+            finished: false,
+        }
+    }
+
+    /// Percentage of completion from 0 to 100. A total of zero is always considered complete.
+    pub fn percent(&self) -> u64 {
+        if self.total == 0 {
+            return 100;
+        }
+
+        (self.position.min(self.total) * 100) / self.total
+    }
+
+    pub fn inc(&mut self, delta: u64) {
+        self.position = self.position.saturating_add(delta).min(self.total);
+    }
+
+    pub fn set_position(&mut self, position: u64) {
+        self.position = position.min(self.total);
+    }
+}
+
+/// Progress handle for formatters that can't redraw lines (Plain and Tree). Instead of a bar it prints a new line
+/// through the formatter every time the progress crosses another [`STEP`](LineProgress::STEP) percent.
+pub(crate) struct LineProgress {
+    inner: Mutex<LineProgressInner>,
+}
+
+struct LineProgressInner {
+    formatter: Box<dyn LinePrinter>,
+    state: ProgressState,
+    last_reported_step: Option<u64>,
+}
+
+impl LineProgress {
+    /// Percentage interval at which a new line is printed.
+    const STEP: u64 = 10;
+
+    pub fn new(formatter: Box<dyn LinePrinter>, total: u64, msg: &dyn Displayable) -> Self {
+        let progress = Self {
+            inner: Mutex::new(LineProgressInner {
+                formatter,
+                state: ProgressState::new(total, msg),
+                last_reported_step: None,
+            }),
+        };
+
+        progress.report(false);
+        progress
+    }
+
+    /// Prints the current progress if it crossed into a new step since the last time it was printed or if `force`
+    /// is set.
+    fn report(&self, force: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        let step = inner.state.percent() / Self::STEP;
+
+        if !force && inner.last_reported_step == Some(step) {
+            return;
+        }
+
+        inner.last_reported_step = Some(step);
+        let line = format!(
+            "{} {}/{} ({}%)",
+            inner.state.message,
+            inner.state.position,
+            inner.state.total,
+            inner.state.percent()
+        );
+        inner.formatter.print_line(LineKind::Info, &line);
+    }
+}
+
+impl ProgressHandle for LineProgress {
+    fn inc(&self, delta: u64) {
+        self.inner.lock().unwrap().state.inc(delta);
+        self.report(false);
+    }
+
+    fn set_position(&self, position: u64) {
+        self.inner.lock().unwrap().state.set_position(position);
+        self.report(false);
+    }
+
+    fn set_message(&self, msg: &dyn Displayable) {
+        self.inner.lock().unwrap().state.message = msg.to_string();
+    }
+
+    fn finish(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state.finished {
+            return;
+        }
+
+        inner.state.finished = true;
+        inner.state.position = inner.state.total;
+        let already_reported = inner.last_reported_step == Some(100 / Self::STEP);
+        drop(inner);
+
+        if !already_reported {
+            self.report(true);
+        }
+    }
+}
+
+/// Progress handle that does nothing. Used by the silent formatter and when progress is filtered out by
+/// [`only`](crate::Formatter::only).
+pub(crate) struct NoopProgress;
+
+impl ProgressHandle for NoopProgress {
+    fn inc(&self, _delta: u64) {}
+    fn set_position(&self, _position: u64) {}
+    fn set_message(&self, _msg: &dyn Displayable) {}
+    fn finish(&self) {}
+}
@@ -0,0 +1,143 @@
+//! Types describing the records written by the [`Json`](crate::Format::Json) and [`Yaml`](crate::Format::Yaml)
+//! formats.
+//!
+//! Every record carries a `schema_version`, which is bumped whenever a record changes in a way that could break
+//! consumers. Adding new kinds of records or new optional keys doesn't count as breaking, so consumers should ignore
+//! labels and keys they don't know about.
+//!
+//! The types can be used to parse records back:
+//!
+//! ```rust
+//! use polyfmt::schema::{Event, Record};
+//!
+//! let line = r#"{"schema_version":1,"label":"success","data":"deployed","ts":"2024-05-01T09:30:00.123Z","elapsed_ms":12,"depth":0,"groups":[]}"#;
+//! let record: Record = serde_json::from_str(line).unwrap();
+//! assert!(matches!(record.event, Event::Success(_)));
+//! ```
+//!
+//! [`json_schema`] describes the same records as a JSON Schema. A copy is kept in the repository as
+//! `schema/record.schema.json` for consumers that aren't written in Rust. The array and the [`Document`] collected by
+//! [`JsonMode::Array`] and [`JsonMode::Document`] are described by [`json_schema_for`], with copies in
+//! `schema/array.schema.json` and `schema/document.schema.json`.
+
+use crate::JsonMode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Version of the record format, written into every record as `schema_version`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A single record written by the Json and Yaml formats.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[schemars(title = "polyfmt record")]
+pub struct Record {
+    /// Version of the record format, bumped whenever a record changes in a way that could break consumers.
+    #[schemars(extend("const" = SCHEMA_VERSION))]
+    pub schema_version: u32,
+
+    /// What happened, tagged by its `label`.
+    #[serde(flatten)]
+    pub event: Event,
+
+    /// Wall-clock time the record was written, as an RFC 3339 timestamp in UTC.
+    #[schemars(extend("format" = "date-time"))]
+    pub ts: String,
+
+    /// Milliseconds since the first formatter of the program was created.
+    pub elapsed_ms: u64,
+
+    /// How deeply the record is indented, `0` for records which aren't indented.
+    pub depth: usize,
+
+    /// Ids of the indentation groups the record is nested in, outermost first. Records in the same group were
+    /// printed within the same indentation. Empty for records which aren't indented.
+    pub groups: Vec<u64>,
+}
+
+/// The kinds of records, told apart by their `label`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "label", rename_all = "lowercase")]
+pub enum Event {
+    /// Regular output from `print` and `println`.
+    Info(Message),
+    Success(Message),
+    Warning(Message),
+    Error(Message),
+    Debug(Message),
+    Trace(Message),
+
+    /// A question the program is waiting for an answer to.
+    Question(Message),
+
+    /// Rows of a table, each keyed by the column header.
+    Table {
+        data: Vec<BTreeMap<String, String>>,
+    },
+
+    /// Progress of a long running operation.
+    Progress {
+        /// Message describing the operation.
+        data: String,
+        current: u64,
+        total: u64,
+    },
+
+    /// Status change of a named task.
+    Task {
+        task: String,
+        status: TaskStatus,
+        data: serde_json::Value,
+    },
+}
+
+/// A message and the structured fields attached to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Message {
+    /// The message, serialized from whatever value was printed.
+    pub data: serde_json::Value,
+
+    /// Fields attached with `with_fields`. Missing when there are none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fields: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+/// The single document written by [`JsonMode::Document`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[schemars(title = "polyfmt document")]
+pub struct Document {
+    /// The regular output: `info` and `table` records.
+    pub results: Vec<Record>,
+
+    /// Every other record.
+    pub messages: Vec<Record>,
+}
+
+/// Status reported by a `task` record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    Started,
+    Running,
+    Success,
+    Error,
+}
+
+/// Generates the JSON Schema for a single [`Record`].
+pub fn json_schema() -> serde_json::Value {
+    schemars::schema_for!(Record).to_value()
+}
+
+/// Generates the JSON Schema for the output of the given mode: a [`Record`] per line for [`JsonMode::Lines`], an
+/// array of them for [`JsonMode::Array`] and a [`Document`] for [`JsonMode::Document`].
+pub fn json_schema_for(mode: JsonMode) -> serde_json::Value {
+    match mode {
+        JsonMode::Lines => json_schema(),
+        JsonMode::Array => {
+            let mut schema = schemars::schema_for!(Vec<Record>);
+            schema.insert("title".to_string(), "polyfmt records".into());
+            schema.to_value()
+        }
+        JsonMode::Document => schemars::schema_for!(Document).to_value(),
+    }
+}
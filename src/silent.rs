@@ -1,4 +1,6 @@
-use crate::{Displayable, Format, Formatter, IndentGuard};
+use crate::progress::NoopProgress;
+use crate::task::NoopTask;
+use crate::{Displayable, Format, Formatter, IndentGuard, ProgressHandle, TaskHandle};
 
 struct Guard;
 
@@ -18,15 +20,23 @@ impl Formatter for Silent {
     fn success(&mut self, _msg: &dyn Displayable) {}
     fn warning(&mut self, _msg: &dyn Displayable) {}
     fn debug(&mut self, _msg: &dyn Displayable) {}
+    fn trace(&mut self, _msg: &dyn Displayable) {}
     fn indent(&mut self) -> Box<dyn crate::IndentGuard> {
         Box::new(Guard {})
     }
     fn outdent(&mut self) {}
+    fn progress(&mut self, _total: u64, _msg: &dyn Displayable) -> Box<dyn ProgressHandle> {
+        Box::new(NoopProgress)
+    }
+    fn task(&mut self, _name: &dyn Displayable) -> Box<dyn TaskHandle> {
+        Box::new(NoopTask)
+    }
 
     fn question(&mut self, _msg: &dyn Displayable) -> String {
         "".to_string()
     }
     fn spacer(&mut self) {}
+    fn table(&mut self, _headers: &[&str], _rows: &[Vec<String>]) {}
     fn pause(&mut self) {}
     fn resume(&mut self) {}
 
@@ -34,5 +44,17 @@ impl Formatter for Silent {
         self
     }
 
+    fn with_fields(&mut self, _fields: &[(&str, &dyn Displayable)]) -> &mut dyn Formatter {
+        self
+    }
+
+    fn with_metadata(&mut self, _fields: &[(&str, &dyn Displayable)]) -> &mut dyn Formatter {
+        self
+    }
+
+    fn format(&self) -> Format {
+        Format::Silent
+    }
+
     fn finish(&self) {}
 }
@@ -1,37 +1,83 @@
 use crate::{
-    format_text_by_length, take_and_check_allowed, Displayable, Format, Formatter, IndentGuard,
-    Options,
+    ansi, format_fields, format_table_columns, format_text_with_fields, take_and_check_allowed,
+    Destination, Displayable, Field, Format, Formatter, IndentGuard, Options, ProgressHandle,
+    Routing, TaskHandle, Theme, Verbosity, WriteErrors,
+};
+use crate::drain::Drain;
+use crate::link::Hyperlinks;
+use crate::progress::NoopProgress;
+use crate::task::NoopTask;
+use crate::timestamp::{Clock, Stamped};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+use std::{
+    collections::HashSet,
+    io::{self, Write},
+    time::Duration,
 };
-use colored::Colorize;
-use indicatif::{ProgressBar, ProgressStyle};
-use std::sync::{Arc, Mutex, Weak};
-use std::{collections::HashSet, io::Write, time::Duration};
 
-#[derive(Clone)]
 pub struct Spinner {
-    debug: bool,
+    verbosity: Verbosity,
     indentation_level: u16,
     max_line_length: usize,
     allowed_formats: HashSet<Format>,
-
+    fields: Vec<Field>,
+    output_target: Arc<Mutex<dyn Write + Send>>,
+    diagnostic_target: Arc<Mutex<dyn Write + Send>>,
+    routing: Routing,
+    write_errors: WriteErrors,
+    theme: Theme,
+    hyperlinks: Hyperlinks,
+    clock: Clock,
+    colors: bool,
+
+    /// Owns every bar drawn by this formatter so that the main spinner and any progress bars can share the
+    /// terminal without clobbering each other.
+    multi: MultiProgress,
     spinner: ProgressBar,
 }
 
 impl Spinner {
     pub fn new(options: Options) -> Arc<Mutex<Self>> {
-        let spinner = ProgressBar::new_spinner();
+        let multi = MultiProgress::new();
+        let spinner = multi.add(ProgressBar::new_spinner());
         spinner.enable_steady_tick(Duration::from_millis(120));
-        spinner.set_style(
-            ProgressStyle::default_spinner()
-                .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"]),
-        );
+        let frames: Vec<&str> = options
+            .theme
+            .spinner_frames
+            .iter()
+            .map(String::as_str)
+            .collect();
+        spinner.set_style(ProgressStyle::default_spinner().tick_strings(&frames));
+
+        let hyperlinks = Hyperlinks::detect(&options.output_target, &options.diagnostic_target);
+        let clock = Clock::new(options.timestamps);
 
         Arc::new(Mutex::new(Spinner {
-            debug: options.debug,
-            max_line_length: options.max_line_length,
+            verbosity: options.verbosity,
+            max_line_length: options.max_line_length.saturating_sub(clock.width()),
             indentation_level: options.padding,
+            multi,
             spinner,
             allowed_formats: HashSet::new(),
+            fields: Vec::new(),
+            output_target: Stamped::wrap(
+                Drain::wrap(options.output_target.target, options.exit_on_broken_pipe),
+                clock,
+            ),
+            diagnostic_target: Stamped::wrap(
+                Drain::wrap(
+                    options.diagnostic_target.target,
+                    options.exit_on_broken_pipe,
+                ),
+                clock,
+            ),
+            routing: options.routing,
+            write_errors: WriteErrors::new(options.write_error_policy),
+            theme: options.theme,
+            hyperlinks,
+            clock,
+            colors: options.color.enabled(),
         }))
     }
 }
@@ -59,118 +105,283 @@ impl Drop for Guard {
     }
 }
 
-impl Spinner {
-    fn print(&mut self, msg: &dyn Displayable) {
-        if !take_and_check_allowed(Format::Spinner, &mut self.allowed_formats) {
-            return;
+/// Progress handle backed by a real indicatif progress bar.
+struct SpinnerProgress {
+    bar: ProgressBar,
+    colors: bool,
+}
+
+impl SpinnerProgress {
+    /// Clamps a position to the total, like [`ProgressState`](crate::progress::ProgressState) does for the other
+    /// formats.
+    fn clamp(&self, position: u64) -> u64 {
+        match self.bar.length() {
+            Some(total) => position.min(total),
+            None => position,
         }
+    }
+}
 
-        self.spinner.set_message(msg.to_string());
+impl ProgressHandle for SpinnerProgress {
+    fn inc(&self, delta: u64) {
+        let position = self.bar.position().saturating_add(delta);
+        self.bar.set_position(self.clamp(position));
     }
 
-    fn println(&mut self, msg: &dyn Displayable) {
-        if !take_and_check_allowed(Format::Spinner, &mut self.allowed_formats) {
+    fn set_position(&self, position: u64) {
+        self.bar.set_position(self.clamp(position));
+    }
+
+    fn set_message(&self, msg: &dyn Displayable) {
+        self.bar.set_message(bar_text(self.colors, msg.to_string()));
+    }
+
+    fn finish(&self) {
+        self.bar.finish();
+    }
+}
+
+/// Bars are drawn by indicatif rather than written to a target, so their text has colors stripped here when they're
+/// turned off.
+fn bar_text(colors: bool, text: String) -> String {
+    if colors {
+        text
+    } else {
+        ansi::strip_colors(&text)
+    }
+}
+
+/// Task handle which owns its own spinner line.
+struct SpinnerTask {
+    name: String,
+    bar: ProgressBar,
+
+    /// Rendered glyphs shown in front of the task once it finishes.
+    success: String,
+    error: String,
+    clock: Clock,
+    colors: bool,
+}
+
+impl SpinnerTask {
+    /// Replaces the spinner with a static line showing the final status of the task.
+    fn finish_with(&self, status: String, msg: &dyn Displayable) {
+        if self.bar.is_finished() {
             return;
         }
 
-        let lines = format_text_by_length(msg, self.indentation_level, self.max_line_length);
+        self.bar.set_style(
+            ProgressStyle::with_template("{prefix}{msg}")
+                .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+        );
+        self.bar
+            .set_prefix(bar_text(self.colors, self.clock.stamp(&self.bar.prefix())));
+        self.bar.finish_with_message(bar_text(
+            self.colors,
+            format!("{status} {} {msg}", ansi::bold(&self.name)),
+        ));
+    }
+}
 
-        if lines.is_empty() {
+impl TaskHandle for SpinnerTask {
+    fn set_message(&self, msg: &dyn Displayable) {
+        if self.bar.is_finished() {
             return;
         }
 
-        self.spinner.println(
-            " ".repeat(self.indentation_level.into()) + lines.first().unwrap_or(&"".to_string()),
+        self.bar.set_message(bar_text(
+            self.colors,
+            format!("{} {msg}", ansi::bold(&self.name)),
+        ));
+    }
+
+    fn success(&self, msg: &dyn Displayable) {
+        self.finish_with(self.success.clone(), msg);
+    }
+
+    fn error(&self, msg: &dyn Displayable) {
+        self.finish_with(self.error.clone(), msg);
+    }
+}
+
+impl Drop for SpinnerTask {
+    fn drop(&mut self) {
+        // A task that was never finished shouldn't keep spinning forever.
+        if !self.bar.is_finished() {
+            self.bar.finish_and_clear();
+        }
+    }
+}
+
+impl Spinner {
+    /// Returns the target for lines routed to the given destination.
+    fn target(&self, destination: Destination) -> &Arc<Mutex<dyn Write + Send>> {
+        match destination {
+            Destination::Output => &self.output_target,
+            Destination::Diagnostic => &self.diagnostic_target,
+        }
+    }
+
+    /// Writes out finished lines with the bars cleared, so the spinner doesn't draw over them and redraws below them
+    /// afterwards.
+    fn write_lines(&self, destination: Destination, lines: Vec<String>) -> io::Result<()> {
+        self.multi.suspend(|| {
+            let mut target = self.target(destination).lock().unwrap();
+            for line in lines {
+                writeln!(target, "{line}")?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn print(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let fields = std::mem::take(&mut self.fields);
+        if !take_and_check_allowed(Format::Spinner, &mut self.allowed_formats)
+            || !self.verbosity.allows(Verbosity::Info)
+        {
+            return Ok(());
+        }
+
+        self.spinner.set_message(bar_text(
+            self.colors,
+            format!("{msg}{}", format_fields(&fields)),
+        ));
+
+        Ok(())
+    }
+
+    fn println(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let fields = std::mem::take(&mut self.fields);
+        if !take_and_check_allowed(Format::Spinner, &mut self.allowed_formats)
+            || !self.verbosity.allows(Verbosity::Info)
+        {
+            return Ok(());
+        }
+
+        let lines = format_text_with_fields(
+            &self.hyperlinks.render(Destination::Output, msg),
+            &fields,
+            self.indentation_level,
+            self.max_line_length,
         );
 
-        for line in lines.iter().skip(1) {
-            self.spinner.println(format!(
-                "{}{}",
-                " ".repeat(self.indentation_level.into()),
-                line
-            ));
+        if lines.is_empty() {
+            return Ok(());
         }
+
+        let rendered = lines
+            .iter()
+            .map(|line| format!("{}{}", " ".repeat(self.indentation_level.into()), line))
+            .collect();
+
+        self.write_lines(Destination::Output, rendered)
     }
 
-    fn error(&mut self, msg: &dyn Displayable) {
+    fn error(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let fields = std::mem::take(&mut self.fields);
         if !take_and_check_allowed(Format::Spinner, &mut self.allowed_formats) {
-            return;
+            return Ok(());
         }
 
-        let lines = format_text_by_length(msg, self.indentation_level + 2, self.max_line_length);
+        let glyph = &self.theme.error;
+        let offset = self.indentation_level + glyph.width() + 1;
+        let lines = format_text_with_fields(
+            &self.hyperlinks.render(self.routing.error, msg),
+            &fields,
+            offset,
+            self.max_line_length,
+        );
 
         if lines.is_empty() {
-            return;
+            return Ok(());
         }
 
-        self.spinner.println(format!(
+        let mut rendered = Vec::new();
+        rendered.push(format!(
             "{}{} {}",
             " ".repeat(self.indentation_level.into()),
-            "x".red(),
+            glyph,
             lines.first().unwrap_or(&"".to_string())
         ));
 
         for line in lines.iter().skip(1) {
-            self.spinner.println(format!(
-                "{}{}",
-                " ".repeat((self.indentation_level + 2).into()),
-                line
-            ));
+            rendered.push(format!("{}{}", " ".repeat(offset.into()), line));
         }
+
+        self.write_lines(self.routing.error, rendered)
     }
 
-    fn success(&mut self, msg: &dyn Displayable) {
-        if !take_and_check_allowed(Format::Spinner, &mut self.allowed_formats) {
-            return;
+    fn success(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let fields = std::mem::take(&mut self.fields);
+        if !take_and_check_allowed(Format::Spinner, &mut self.allowed_formats)
+            || !self.verbosity.allows(Verbosity::Info)
+        {
+            return Ok(());
         }
 
-        let lines = format_text_by_length(msg, self.indentation_level + 2, self.max_line_length);
+        let glyph = &self.theme.success;
+        let offset = self.indentation_level + glyph.width() + 1;
+        let lines = format_text_with_fields(
+            &self.hyperlinks.render(self.routing.success, msg),
+            &fields,
+            offset,
+            self.max_line_length,
+        );
 
         if lines.is_empty() {
-            return;
+            return Ok(());
         }
 
-        self.spinner.println(format!(
+        let mut rendered = Vec::new();
+        rendered.push(format!(
             "{}{} {}",
             " ".repeat(self.indentation_level.into()),
-            "✓".green(),
+            glyph,
             lines.first().unwrap_or(&"".to_string())
         ));
 
         for line in lines.iter().skip(1) {
-            self.spinner.println(format!(
-                "{}{}",
-                " ".repeat((self.indentation_level + 2).into()),
-                line
-            ));
+            rendered.push(format!("{}{}", " ".repeat(offset.into()), line));
         }
+
+        self.write_lines(self.routing.success, rendered)
     }
 
-    fn warning(&mut self, msg: &dyn Displayable) {
-        if !take_and_check_allowed(Format::Spinner, &mut self.allowed_formats) {
-            return;
+    fn warning(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let fields = std::mem::take(&mut self.fields);
+        if !take_and_check_allowed(Format::Spinner, &mut self.allowed_formats)
+            || !self.verbosity.allows(Verbosity::Warn)
+        {
+            return Ok(());
         }
 
-        let lines = format_text_by_length(msg, self.indentation_level + 2, self.max_line_length);
+        let glyph = &self.theme.warning;
+        let offset = self.indentation_level + glyph.width() + 1;
+        let lines = format_text_with_fields(
+            &self.hyperlinks.render(self.routing.warning, msg),
+            &fields,
+            offset,
+            self.max_line_length,
+        );
 
         if lines.is_empty() {
-            return;
+            return Ok(());
         }
 
-        self.spinner.println(format!(
+        let mut rendered = Vec::new();
+        rendered.push(format!(
             "{}{} {}",
             " ".repeat(self.indentation_level.into()),
-            "!!".yellow(),
+            glyph,
             lines.first().unwrap_or(&"".to_string())
         ));
 
         for line in lines.iter().skip(1) {
-            self.spinner.println(format!(
-                "{}{}",
-                " ".repeat((self.indentation_level + 3).into()),
-                line
-            ));
+            rendered.push(format!("{}{}", " ".repeat(offset.into()), line));
         }
+
+        self.write_lines(self.routing.warning, rendered)
     }
 
     fn indent(spinner: &Arc<Mutex<Self>>) -> Box<dyn IndentGuard> {
@@ -182,14 +393,107 @@ impl Spinner {
         Box::new(guard)
     }
 
+    fn progress(&mut self, total: u64, msg: &dyn Displayable) -> Box<dyn ProgressHandle> {
+        self.fields.clear();
+        if !take_and_check_allowed(Format::Spinner, &mut self.allowed_formats)
+            || !self.verbosity.allows(Verbosity::Info)
+        {
+            return Box::new(NoopProgress);
+        }
+
+        // Progress bars are drawn above the main spinner so the spinner always stays on the last line.
+        let bar = self
+            .multi
+            .insert_before(&self.spinner, ProgressBar::new(total));
+        bar.set_style(
+            ProgressStyle::with_template(&format!(
+                "{}{{msg}} [{{bar:30}}] {{pos}}/{{len}} ({{eta}})",
+                " ".repeat(self.indentation_level.into())
+            ))
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
+            .progress_chars("=> "),
+        );
+        bar.set_message(bar_text(self.colors, msg.to_string()));
+
+        Box::new(SpinnerProgress {
+            bar,
+            colors: self.colors,
+        })
+    }
+
+    fn task(&mut self, name: &dyn Displayable) -> Box<dyn TaskHandle> {
+        self.fields.clear();
+        if !take_and_check_allowed(Format::Spinner, &mut self.allowed_formats)
+            || !self.verbosity.allows(Verbosity::Info)
+        {
+            return Box::new(NoopTask);
+        }
+
+        let name = name.to_string();
+        let bar = self
+            .multi
+            .insert_before(&self.spinner, ProgressBar::new_spinner());
+
+        // The last frame is shown once the spinner stops, which should leave nothing behind.
+        let mut frames: Vec<&str> = self
+            .theme
+            .spinner_frames
+            .iter()
+            .map(String::as_str)
+            .collect();
+        frames.push(" ");
+        bar.set_style(
+            ProgressStyle::with_template("{prefix}{spinner} {wide_msg}")
+                .unwrap_or_else(|_| ProgressStyle::default_spinner())
+                .tick_strings(&frames),
+        );
+        bar.set_prefix(" ".repeat(self.indentation_level.into()));
+        bar.set_message(bar_text(self.colors, ansi::bold(&name)));
+        bar.enable_steady_tick(Duration::from_millis(120));
+
+        Box::new(SpinnerTask {
+            name,
+            bar,
+            success: self.theme.success.to_string(),
+            error: self.theme.error.to_string(),
+            clock: self.clock,
+            colors: self.colors,
+        })
+    }
+
     fn outdent(&mut self) {
         if self.indentation_level > 0 {
             self.indentation_level -= 1;
         }
     }
 
-    fn spacer(&mut self) {
-        self.spinner.println("");
+    fn spacer(&mut self) -> io::Result<()> {
+        if !self.verbosity.allows(Verbosity::Info) {
+            return Ok(());
+        }
+
+        self.write_lines(Destination::Output, vec![String::new()])
+    }
+
+    fn table(&mut self, headers: &[&str], rows: &[Vec<String>]) -> io::Result<()> {
+        self.fields.clear();
+        if !take_and_check_allowed(Format::Spinner, &mut self.allowed_formats)
+            || !self.verbosity.allows(Verbosity::Info)
+        {
+            return Ok(());
+        }
+
+        let max_width = self
+            .max_line_length
+            .saturating_sub(self.indentation_level.into());
+        let (header, body) = format_table_columns(headers, rows, max_width, &self.theme.ellipsis);
+
+        let rendered = std::iter::once(ansi::bold(&header))
+            .chain(body)
+            .map(|line| format!("{}{}", " ".repeat(self.indentation_level.into()), line))
+            .collect();
+
+        self.write_lines(Destination::Output, rendered)
     }
 
     fn pause(&mut self) {
@@ -200,77 +504,116 @@ impl Spinner {
         self.spinner.enable_steady_tick(Duration::from_millis(120));
     }
 
-    fn debug(&mut self, msg: &dyn Displayable) {
-        if !take_and_check_allowed(Format::Spinner, &mut self.allowed_formats) || !self.debug {
-            return;
+    fn debug(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        self.leveled(Verbosity::Debug, msg)
+    }
+
+    fn trace(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        self.leveled(Verbosity::Trace, msg)
+    }
+
+    /// Prints debug and trace lines, which only differ in their glyph and the verbosity they need.
+    fn leveled(&mut self, level: Verbosity, msg: &dyn Displayable) -> io::Result<()> {
+        let fields = std::mem::take(&mut self.fields);
+        if !take_and_check_allowed(Format::Spinner, &mut self.allowed_formats)
+            || !self.verbosity.allows(level)
+        {
+            return Ok(());
         }
 
-        let lines = format_text_by_length(msg, self.indentation_level + 8, self.max_line_length);
+        let glyph = match level {
+            Verbosity::Trace => &self.theme.trace,
+            _ => &self.theme.debug,
+        };
+        let offset = self.indentation_level + glyph.width() + 1;
+        let lines = format_text_with_fields(
+            &self.hyperlinks.render(self.routing.debug, msg),
+            &fields,
+            offset,
+            self.max_line_length,
+        );
 
         if lines.is_empty() {
-            return;
+            return Ok(());
         }
 
-        self.spinner.println(format!(
+        let mut rendered = Vec::new();
+        rendered.push(format!(
             "{}{} {}",
             " ".repeat(self.indentation_level.into()),
-            "[debug]".dimmed(),
+            glyph,
             lines.first().unwrap_or(&"".to_string())
         ));
 
         for line in lines.iter().skip(1) {
-            self.spinner.println(format!(
-                "{}{}",
-                " ".repeat((self.indentation_level + 8).into()),
-                line
-            ));
+            rendered.push(format!("{}{}", " ".repeat(offset.into()), line));
         }
+
+        self.write_lines(self.routing.debug, rendered)
     }
 
-    fn question(&mut self, msg: &dyn Displayable) -> String {
+    fn question(&mut self, msg: &dyn Displayable) -> io::Result<String> {
+        let fields = std::mem::take(&mut self.fields);
         if !take_and_check_allowed(Format::Spinner, &mut self.allowed_formats) {
-            return "".to_string();
+            return Ok("".to_string());
         }
 
-        let lines = format_text_by_length(msg, self.indentation_level + 2, self.max_line_length);
+        let glyph = &self.theme.question;
+        let offset = self.indentation_level + glyph.width() + 1;
+        let lines = format_text_with_fields(
+            &self.hyperlinks.render(self.routing.question, msg),
+            &fields,
+            offset,
+            self.max_line_length,
+        );
 
         let mut input = String::from("");
 
-        self.spinner.suspend(|| {
+        self.spinner.suspend(|| -> io::Result<()> {
+            let mut target = self
+                .target(self.routing.question)
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+
             if lines.len() == 1 {
-                print!(
+                write!(
+                    target,
                     "{}{} {}",
                     " ".repeat(self.indentation_level.into()),
-                    "?".magenta(),
+                    glyph,
                     lines.first().unwrap_or(&"".to_string()),
-                );
+                )?;
             } else {
-                println!(
+                writeln!(
+                    target,
                     "{}{} {}",
                     " ".repeat(self.indentation_level.into()),
-                    "?".magenta(),
+                    glyph,
                     lines.first().unwrap_or(&"".to_string()),
-                );
+                )?;
 
-                // Print the remaining lines except the last with println!
+                // Print the remaining lines except the last with writeln!
                 let lines_count = lines.len();
                 for (index, line) in lines.iter().enumerate().skip(1) {
                     if index + 1 < lines_count {
                         // Not the last line
-                        println!("{} {}", " ".repeat(self.indentation_level.into()), line);
+                        writeln!(target, "{}{}", " ".repeat(offset.into()), line)?;
                     } else {
-                        // Last line, use print! instead
-                        print!("{} {}", " ".repeat(self.indentation_level.into()), line);
+                        // Last line, use write! instead
+                        write!(target, "{}{}", " ".repeat(offset.into()), line)?;
                     }
                 }
             }
 
-            std::io::stdout().flush().unwrap();
+            target.flush()?;
+            drop(target);
+
+            std::io::stdin().read_line(&mut input)?;
 
-            let _ = std::io::stdin().read_line(&mut input);
-        });
+            Ok(())
+        })?;
 
-        input.trim().to_string()
+        Ok(input.trim().to_string())
     }
 
     fn only(&mut self, types: Vec<Format>) -> &mut Self {
@@ -278,46 +621,79 @@ impl Spinner {
         self
     }
 
-    fn finish(&self) {
+    fn with_fields(&mut self, fields: &[(&str, &dyn Displayable)]) -> &mut Self {
+        self.fields = fields
+            .iter()
+            .map(|(key, value)| Field::new(key, *value))
+            .collect();
+        self
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
         self.spinner.finish_and_clear();
+
+        self.output_target.lock().unwrap().flush()?;
+        self.diagnostic_target.lock().unwrap().flush()
     }
 }
 
 impl Formatter for Arc<Mutex<Spinner>> {
     fn print(&mut self, msg: &dyn Displayable) {
         let mut fmt = self.lock().unwrap();
-        fmt.print(msg);
+        let result = fmt.print(msg);
+        fmt.write_errors.handle(result);
     }
 
     fn println(&mut self, msg: &dyn Displayable) {
         let mut fmt = self.lock().unwrap();
-        fmt.println(msg);
+        let result = fmt.println(msg);
+        fmt.write_errors.handle(result);
     }
 
     fn error(&mut self, msg: &dyn Displayable) {
         let mut fmt = self.lock().unwrap();
-        fmt.error(msg);
+        let result = fmt.error(msg);
+        fmt.write_errors.handle(result);
     }
 
     fn success(&mut self, msg: &dyn Displayable) {
         let mut fmt = self.lock().unwrap();
-        fmt.success(msg);
+        let result = fmt.success(msg);
+        fmt.write_errors.handle(result);
     }
 
     fn warning(&mut self, msg: &dyn Displayable) {
         let mut fmt = self.lock().unwrap();
-        fmt.warning(msg);
+        let result = fmt.warning(msg);
+        fmt.write_errors.handle(result);
     }
 
     fn debug(&mut self, msg: &dyn Displayable) {
         let mut fmt = self.lock().unwrap();
-        fmt.debug(msg);
+        let result = fmt.debug(msg);
+        fmt.write_errors.handle(result);
+    }
+
+    fn trace(&mut self, msg: &dyn Displayable) {
+        let mut fmt = self.lock().unwrap();
+        let result = fmt.trace(msg);
+        fmt.write_errors.handle(result);
     }
 
     fn indent(&mut self) -> Box<dyn IndentGuard> {
         Spinner::indent(self)
     }
 
+    fn progress(&mut self, total: u64, msg: &dyn Displayable) -> Box<dyn ProgressHandle> {
+        let mut fmt = self.lock().unwrap();
+        fmt.progress(total, msg)
+    }
+
+    fn task(&mut self, name: &dyn Displayable) -> Box<dyn TaskHandle> {
+        let mut fmt = self.lock().unwrap();
+        fmt.task(name)
+    }
+
     fn outdent(&mut self) {
         let mut fmt = self.lock().unwrap();
         fmt.outdent();
@@ -325,7 +701,14 @@ impl Formatter for Arc<Mutex<Spinner>> {
 
     fn spacer(&mut self) {
         let mut fmt = self.lock().unwrap();
-        fmt.spacer()
+        let result = fmt.spacer();
+        fmt.write_errors.handle(result);
+    }
+
+    fn table(&mut self, headers: &[&str], rows: &[Vec<String>]) {
+        let mut fmt = self.lock().unwrap();
+        let result = fmt.table(headers, rows);
+        fmt.write_errors.handle(result);
     }
 
     fn pause(&mut self) {
@@ -340,7 +723,8 @@ impl Formatter for Arc<Mutex<Spinner>> {
 
     fn question(&mut self, msg: &dyn Displayable) -> String {
         let mut fmt = self.lock().unwrap();
-        fmt.question(msg)
+        let result = fmt.question(msg);
+        fmt.write_errors.handle(result)
     }
 
     fn only(&mut self, types: Vec<Format>) -> &mut dyn Formatter {
@@ -350,8 +734,80 @@ impl Formatter for Arc<Mutex<Spinner>> {
         self
     }
 
+    fn with_fields(&mut self, fields: &[(&str, &dyn Displayable)]) -> &mut dyn Formatter {
+        let mut fmt = self.lock().unwrap();
+        fmt.with_fields(fields);
+        drop(fmt);
+        self
+    }
+
+    fn with_metadata(&mut self, _fields: &[(&str, &dyn Displayable)]) -> &mut dyn Formatter {
+        self
+    }
+
+    fn format(&self) -> Format {
+        Format::Spinner
+    }
+
     fn finish(&self) {
-        let fmt = self.lock().unwrap();
-        fmt.finish();
+        let mut fmt = self.lock().unwrap();
+        let result = fmt.finish();
+        fmt.write_errors.handle(result);
+    }
+
+    fn try_print(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.print(msg)
+    }
+
+    fn try_println(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.println(msg)
+    }
+
+    fn try_error(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.error(msg)
+    }
+
+    fn try_success(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.success(msg)
+    }
+
+    fn try_warning(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.warning(msg)
+    }
+
+    fn try_debug(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.debug(msg)
+    }
+
+    fn try_trace(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.trace(msg)
+    }
+
+    fn try_spacer(&mut self) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.spacer()
+    }
+
+    fn try_table(&mut self, headers: &[&str], rows: &[Vec<String>]) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        fmt.table(headers, rows)
+    }
+
+    fn try_question(&mut self, msg: &dyn Displayable) -> io::Result<String> {
+        let mut fmt = self.lock().unwrap();
+        fmt.question(msg)
+    }
+
+    fn try_finish(&self) -> io::Result<()> {
+        let mut fmt = self.lock().unwrap();
+        let result = fmt.finish();
+        fmt.write_errors.take().and(result)
     }
 }
@@ -0,0 +1,77 @@
+use crate::progress::{LineKind, LinePrinter};
+use crate::{Displayable, TaskHandle};
+use std::sync::Mutex;
+
+/// Task handle for formatters that can't redraw lines (Plain and Tree). Every update is printed as a new line
+/// through the formatter, prefixed with the task name so that interleaved tasks can be told apart.
+pub(crate) struct LineTask {
+    name: String,
+    inner: Mutex<LineTaskInner>,
+}
+
+struct LineTaskInner {
+    formatter: Box<dyn LinePrinter>,
+    finished: bool,
+}
+
+impl LineTask {
+    pub fn new(formatter: Box<dyn LinePrinter>, name: &dyn Displayable) -> Self {
+        let name = name.to_string();
+        formatter.print_line(LineKind::Info, &format!("[{name}] started"));
+
+        Self {
+            name,
+            inner: Mutex::new(LineTaskInner {
+                formatter,
+                finished: false,
+            }),
+        }
+    }
+}
+
+impl TaskHandle for LineTask {
+    fn set_message(&self, msg: &dyn Displayable) {
+        let inner = self.inner.lock().unwrap();
+        if inner.finished {
+            return;
+        }
+
+        inner
+            .formatter
+            .print_line(LineKind::Info, &format!("[{}] {msg}", self.name));
+    }
+
+    fn success(&self, msg: &dyn Displayable) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.finished {
+            return;
+        }
+
+        inner.finished = true;
+        inner
+            .formatter
+            .print_line(LineKind::Success, &format!("[{}] {msg}", self.name));
+    }
+
+    fn error(&self, msg: &dyn Displayable) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.finished {
+            return;
+        }
+
+        inner.finished = true;
+        inner
+            .formatter
+            .print_line(LineKind::Error, &format!("[{}] {msg}", self.name));
+    }
+}
+
+/// Task handle that does nothing. Used by the silent formatter and when a task is filtered out by
+/// [`only`](crate::Formatter::only).
+pub(crate) struct NoopTask;
+
+impl TaskHandle for NoopTask {
+    fn set_message(&self, _msg: &dyn Displayable) {}
+    fn success(&self, _msg: &dyn Displayable) {}
+    fn error(&self, _msg: &dyn Displayable) {}
+}
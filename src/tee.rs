@@ -0,0 +1,327 @@
+use crate::{
+    with_owned_fields, with_owned_metadata, Displayable, Field, Format, Formatter, IndentGuard,
+    ProgressHandle, TaskHandle,
+};
+use std::collections::HashSet;
+use std::io;
+
+/// Formatter which fans every call out to several other formatters at once.
+///
+/// This is useful when the same run should produce more than one kind of output, for example human readable output
+/// on the terminal and a JSON log written to a file.
+///
+/// Filters set with [`only`](Formatter::only) are checked against each child's own format, so
+/// `only(vec![Format::Json])` only reaches the json children. Questions are only asked by a single interactive
+/// child (the first one by default, see [`with_interactive`](Tee::with_interactive)).
+///
+/// # Example
+///
+/// ```
+/// use polyfmt::{new, Format, Formatter, Options, Tee};
+///
+/// let log = Options::default().with_custom_output_target(Vec::new());
+/// let mut fmt = Tee::new(vec![
+///     new(Format::Plain, Options::default()),
+///     new(Format::Json, log),
+/// ]);
+///
+/// fmt.println(&"written to both formatters");
+/// fmt.only(vec![Format::Json]).println(&"only written to the json formatter");
+/// fmt.finish();
+/// ```
+pub struct Tee {
+    formatters: Vec<Box<dyn Formatter>>,
+    interactive: usize,
+    allowed_formats: HashSet<Format>,
+    fields: Vec<Field>,
+    metadata: Vec<Field>,
+}
+
+impl Tee {
+    pub fn new(formatters: Vec<Box<dyn Formatter>>) -> Self {
+        Tee {
+            formatters,
+            interactive: 0,
+            allowed_formats: HashSet::new(),
+            fields: Vec::new(),
+            metadata: Vec::new(),
+        }
+    }
+
+    /// Sets which child (by index) is responsible for asking questions and reading user input. Defaults to the
+    /// first child.
+    pub fn with_interactive(self, index: usize) -> Self {
+        Self {
+            interactive: index,
+            ..self
+        }
+    }
+
+    /// Runs `f` against every child that passes the pending filter, attaching any pending fields and metadata. Leaves
+    /// the filter, fields and metadata empty regardless.
+    fn each(&mut self, mut f: impl FnMut(&mut dyn Formatter)) {
+        let allowed = std::mem::take(&mut self.allowed_formats);
+        let fields = std::mem::take(&mut self.fields);
+        let metadata = std::mem::take(&mut self.metadata);
+
+        for formatter in &mut self.formatters {
+            if !allowed.is_empty() && !allowed.contains(&formatter.format()) {
+                continue;
+            }
+
+            let formatter = with_owned_fields(formatter.as_mut(), &fields);
+            f(with_owned_metadata(formatter, &metadata));
+        }
+    }
+
+    /// Like [`each`](Tee::each) but for fallible calls. Every child is called even if an earlier one fails; the
+    /// first error is returned.
+    fn try_each(
+        &mut self,
+        mut f: impl FnMut(&mut dyn Formatter) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let mut result = Ok(());
+        self.each(|fmt| {
+            let outcome = f(fmt);
+            if result.is_ok() {
+                result = outcome;
+            }
+        });
+
+        result
+    }
+
+    /// Runs `f` against the interactive child if it passes the pending filter, attaching any pending fields and
+    /// metadata. Leaves the filter, fields and metadata empty regardless.
+    fn with_interactive_child<T>(&mut self, f: impl FnOnce(&mut dyn Formatter) -> T) -> Option<T> {
+        let allowed = std::mem::take(&mut self.allowed_formats);
+        let fields = std::mem::take(&mut self.fields);
+        let metadata = std::mem::take(&mut self.metadata);
+
+        let formatter = self.formatters.get_mut(self.interactive)?;
+        if !allowed.is_empty() && !allowed.contains(&formatter.format()) {
+            return None;
+        }
+
+        let formatter = with_owned_fields(formatter.as_mut(), &fields);
+        Some(f(with_owned_metadata(formatter, &metadata)))
+    }
+}
+
+/// Holds the indentation guards of every child so they're all dropped together.
+struct Guard {
+    _guards: Vec<Box<dyn IndentGuard>>,
+}
+
+impl IndentGuard for Guard {}
+
+struct TeeProgress(Vec<Box<dyn ProgressHandle>>);
+
+impl ProgressHandle for TeeProgress {
+    fn inc(&self, delta: u64) {
+        self.0.iter().for_each(|progress| progress.inc(delta));
+    }
+
+    fn set_position(&self, position: u64) {
+        self.0
+            .iter()
+            .for_each(|progress| progress.set_position(position));
+    }
+
+    fn set_message(&self, msg: &dyn Displayable) {
+        self.0.iter().for_each(|progress| progress.set_message(msg));
+    }
+
+    fn finish(&self) {
+        self.0.iter().for_each(|progress| progress.finish());
+    }
+}
+
+struct TeeTask(Vec<Box<dyn TaskHandle>>);
+
+impl TaskHandle for TeeTask {
+    fn set_message(&self, msg: &dyn Displayable) {
+        self.0.iter().for_each(|task| task.set_message(msg));
+    }
+
+    fn success(&self, msg: &dyn Displayable) {
+        self.0.iter().for_each(|task| task.success(msg));
+    }
+
+    fn error(&self, msg: &dyn Displayable) {
+        self.0.iter().for_each(|task| task.error(msg));
+    }
+}
+
+impl Formatter for Tee {
+    fn print(&mut self, msg: &dyn Displayable) {
+        self.each(|fmt| fmt.print(msg));
+    }
+
+    fn println(&mut self, msg: &dyn Displayable) {
+        self.each(|fmt| fmt.println(msg));
+    }
+
+    fn error(&mut self, msg: &dyn Displayable) {
+        self.each(|fmt| fmt.error(msg));
+    }
+
+    fn success(&mut self, msg: &dyn Displayable) {
+        self.each(|fmt| fmt.success(msg));
+    }
+
+    fn warning(&mut self, msg: &dyn Displayable) {
+        self.each(|fmt| fmt.warning(msg));
+    }
+
+    fn debug(&mut self, msg: &dyn Displayable) {
+        self.each(|fmt| fmt.debug(msg));
+    }
+
+    fn trace(&mut self, msg: &dyn Displayable) {
+        self.each(|fmt| fmt.trace(msg));
+    }
+
+    fn indent(&mut self) -> Box<dyn IndentGuard> {
+        let guards = self
+            .formatters
+            .iter_mut()
+            .map(|formatter| formatter.indent())
+            .collect();
+
+        Box::new(Guard { _guards: guards })
+    }
+
+    fn outdent(&mut self) {
+        self.formatters
+            .iter_mut()
+            .for_each(|formatter| formatter.outdent());
+    }
+
+    fn spacer(&mut self) {
+        self.each(|fmt| fmt.spacer());
+    }
+
+    fn table(&mut self, headers: &[&str], rows: &[Vec<String>]) {
+        self.each(|fmt| fmt.table(headers, rows));
+    }
+
+    fn progress(&mut self, total: u64, msg: &dyn Displayable) -> Box<dyn ProgressHandle> {
+        let mut handles = Vec::new();
+        self.each(|fmt| handles.push(fmt.progress(total, msg)));
+        Box::new(TeeProgress(handles))
+    }
+
+    fn task(&mut self, name: &dyn Displayable) -> Box<dyn TaskHandle> {
+        let mut handles = Vec::new();
+        self.each(|fmt| handles.push(fmt.task(name)));
+        Box::new(TeeTask(handles))
+    }
+
+    fn pause(&mut self) {
+        self.formatters
+            .iter_mut()
+            .for_each(|formatter| formatter.pause());
+    }
+
+    fn resume(&mut self) {
+        self.formatters
+            .iter_mut()
+            .for_each(|formatter| formatter.resume());
+    }
+
+    fn question(&mut self, msg: &dyn Displayable) -> String {
+        self.with_interactive_child(|fmt| fmt.question(msg))
+            .unwrap_or_default()
+    }
+
+    fn only(&mut self, types: Vec<Format>) -> &mut dyn Formatter {
+        self.allowed_formats = types.into_iter().collect();
+        self
+    }
+
+    fn with_fields(&mut self, fields: &[(&str, &dyn Displayable)]) -> &mut dyn Formatter {
+        self.fields = fields
+            .iter()
+            .map(|(key, value)| Field::new(key, *value))
+            .collect();
+        self
+    }
+
+    /// Passes the metadata on to every child, each of which decides for itself whether to print it.
+    fn with_metadata(&mut self, fields: &[(&str, &dyn Displayable)]) -> &mut dyn Formatter {
+        self.metadata = fields
+            .iter()
+            .map(|(key, value)| Field::new(key, *value))
+            .collect();
+        self
+    }
+
+    /// Returns the format of the interactive child, since that is the one users are looking at.
+    fn format(&self) -> Format {
+        self.formatters
+            .get(self.interactive)
+            .map(|formatter| formatter.format())
+            .unwrap_or_default()
+    }
+
+    fn finish(&self) {
+        self.formatters
+            .iter()
+            .for_each(|formatter| formatter.finish());
+    }
+
+    fn try_print(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        self.try_each(|fmt| fmt.try_print(msg))
+    }
+
+    fn try_println(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        self.try_each(|fmt| fmt.try_println(msg))
+    }
+
+    fn try_error(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        self.try_each(|fmt| fmt.try_error(msg))
+    }
+
+    fn try_success(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        self.try_each(|fmt| fmt.try_success(msg))
+    }
+
+    fn try_warning(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        self.try_each(|fmt| fmt.try_warning(msg))
+    }
+
+    fn try_debug(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        self.try_each(|fmt| fmt.try_debug(msg))
+    }
+
+    fn try_trace(&mut self, msg: &dyn Displayable) -> io::Result<()> {
+        self.try_each(|fmt| fmt.try_trace(msg))
+    }
+
+    fn try_spacer(&mut self) -> io::Result<()> {
+        self.try_each(|fmt| fmt.try_spacer())
+    }
+
+    fn try_table(&mut self, headers: &[&str], rows: &[Vec<String>]) -> io::Result<()> {
+        self.try_each(|fmt| fmt.try_table(headers, rows))
+    }
+
+    fn try_question(&mut self, msg: &dyn Displayable) -> io::Result<String> {
+        self.with_interactive_child(|fmt| fmt.try_question(msg))
+            .unwrap_or_else(|| Ok("".to_string()))
+    }
+
+    /// Finishes every child, returning the first error.
+    fn try_finish(&self) -> io::Result<()> {
+        let mut result = Ok(());
+        for formatter in &self.formatters {
+            let outcome = formatter.try_finish();
+            if result.is_ok() {
+                result = outcome;
+            }
+        }
+
+        result
+    }
+}
@@ -0,0 +1,564 @@
+//! Helpers for testing code which prints through polyfmt.
+//!
+//! [`Capture`] is a formatter which doesn't print anything. Instead it records every call as a typed [`Event`] so
+//! tests can assert on what was printed without matching against ANSI colored text. The recorded events can also be
+//! replayed through any real format with [`Capture::render`] for snapshot style comparisons.
+//!
+//! ```
+//! use polyfmt::testing::{Capture, EventKind};
+//! use polyfmt::Formatter;
+//!
+//! let capture = Capture::new();
+//! let mut fmt: Box<dyn Formatter> = Box::new(capture.clone());
+//!
+//! fmt.println(&"deploying");
+//! {
+//!     let _guard = fmt.indent();
+//!     fmt.with_fields(&[("id", &42)]).error(&"failed to fetch");
+//! }
+//!
+//! capture.assert_messages(&[
+//!     (EventKind::Println, "deploying"),
+//!     (EventKind::Error, "failed to fetch"),
+//! ]);
+//! assert_eq!(capture.events()[1].depth, 1);
+//! ```
+
+use crate::progress::ProgressState;
+use crate::{
+    Displayable, Field, Format, Formatter, IndentGuard, Options, ProgressHandle, RenderedValue,
+    TaskHandle, new, with_owned_fields, with_owned_metadata,
+};
+use serde_json::json;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex, Weak};
+
+/// Which formatter call produced an [`Event`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    Print,
+    Println,
+    Error,
+    Success,
+    Warning,
+    Debug,
+    Trace,
+    Question,
+    Spacer,
+    Table,
+    Progress,
+    Task,
+}
+
+/// A single recorded formatter call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub kind: EventKind,
+
+    /// The message as it would be displayed by a human readable formatter.
+    pub message: String,
+
+    /// The message as it would be serialized by a machine readable formatter. For tables this holds the headers and
+    /// rows, for progress the current position and total and for tasks the task name and status.
+    pub data: serde_json::Value,
+
+    /// Indentation level at the time of the call.
+    pub depth: u16,
+
+    /// Formats passed to [`only`](Formatter::only) right before the call. Empty means the call wasn't filtered.
+    pub only: Vec<Format>,
+
+    /// Fields passed to [`with_fields`](Formatter::with_fields) right before the call.
+    pub fields: Vec<(String, serde_json::Value)>,
+
+    /// Fields passed to [`with_metadata`](Formatter::with_metadata) right before the call.
+    pub metadata: Vec<(String, serde_json::Value)>,
+}
+
+impl Event {
+    /// Returns true if a formatter of the given format would have printed this event, going by the
+    /// [`only`](Formatter::only) filter.
+    pub fn applies_to(&self, format: &Format) -> bool {
+        self.only.is_empty() || self.only.contains(format)
+    }
+}
+
+/// A cloneable, thread safe in-memory writer. Useful as a custom output target in tests, since a clone can be handed
+/// to [`Options::with_custom_output_target`] while the original is kept around to read the output.
+#[derive(Clone, Default)]
+pub struct SharedBuffer {
+    inner: Arc<Mutex<Vec<u8>>>,
+}
+
+impl SharedBuffer {
+    /// Returns everything written so far.
+    pub fn contents(&self) -> String {
+        let inner = self.inner.lock().unwrap();
+        String::from_utf8_lossy(&inner).to_string()
+    }
+
+    /// Consumes the buffer, returning everything written to it.
+    pub fn into_string(self) -> String {
+        self.contents()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Formatter which records every call as an [`Event`] instead of printing it.
+///
+/// Clones share the same recording, so keep a clone around to inspect events after handing the formatter off
+/// (for example to [`set_global_formatter`](crate::set_global_formatter)).
+#[derive(Clone, Default)]
+pub struct Capture {
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Default)]
+struct Inner {
+    format: Format,
+    events: Vec<Event>,
+    depth: u16,
+    only: Vec<Format>,
+    fields: Vec<Field>,
+    metadata: Vec<Field>,
+    answers: VecDeque<String>,
+}
+
+impl Inner {
+    fn record(&mut self, kind: EventKind, message: String, data: serde_json::Value) {
+        let event = Event {
+            kind,
+            message,
+            data,
+            depth: self.depth,
+            only: std::mem::take(&mut self.only),
+            fields: std::mem::take(&mut self.fields)
+                .into_iter()
+                .map(|field| (field.key, field.value))
+                .collect(),
+            metadata: std::mem::take(&mut self.metadata)
+                .into_iter()
+                .map(|field| (field.key, field.value))
+                .collect(),
+        };
+
+        self.events.push(event);
+    }
+
+    fn record_message(&mut self, kind: EventKind, msg: &dyn Displayable) {
+        let data = serde_json::to_value(msg.as_serialize())
+            .unwrap_or_else(|_| serde_json::Value::String(msg.to_string()));
+        self.record(kind, msg.to_string(), data);
+    }
+}
+
+impl Capture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the format the capture reports from [`Formatter::format`]. Defaults to [`Format::Plain`]. This doesn't
+    /// change what gets recorded, but code that branches on the format will take the matching branch.
+    pub fn with_format(self, format: Format) -> Self {
+        self.inner.lock().unwrap().format = format;
+        self
+    }
+
+    /// Queues answers returned by [`question`](Formatter::question), in order. Once the answers run out questions
+    /// return an empty string.
+    pub fn with_answers<I, S>(self, answers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.inner
+            .lock()
+            .unwrap()
+            .answers
+            .extend(answers.into_iter().map(Into::into));
+        self
+    }
+
+    /// Returns every event recorded so far.
+    pub fn events(&self) -> Vec<Event> {
+        self.inner.lock().unwrap().events.clone()
+    }
+
+    /// Returns the kind and message of every event recorded so far.
+    pub fn messages(&self) -> Vec<(EventKind, String)> {
+        self.inner
+            .lock()
+            .unwrap()
+            .events
+            .iter()
+            .map(|event| (event.kind, event.message.clone()))
+            .collect()
+    }
+
+    /// Forgets every event recorded so far.
+    pub fn clear(&self) {
+        self.inner.lock().unwrap().events.clear();
+    }
+
+    /// Asserts that exactly the given kinds and messages were recorded, in order.
+    #[track_caller]
+    pub fn assert_messages(&self, expected: &[(EventKind, &str)]) {
+        let expected: Vec<(EventKind, String)> = expected
+            .iter()
+            .map(|(kind, message)| (*kind, message.to_string()))
+            .collect();
+
+        assert_eq!(self.messages(), expected, "captured messages differ");
+    }
+
+    /// Asserts that at least one event of the given kind contains `needle` in its message.
+    #[track_caller]
+    pub fn assert_contains(&self, kind: EventKind, needle: &str) {
+        let messages = self.messages();
+        let found = messages
+            .iter()
+            .any(|(event_kind, message)| *event_kind == kind && message.contains(needle));
+
+        assert!(
+            found,
+            "no {kind:?} event containing {needle:?} was captured, got: {messages:#?}"
+        );
+    }
+
+    /// Replays the recorded events through a real formatter of the given format and returns the output.
+    ///
+    /// The output target of `options` is replaced with an in-memory buffer and [`Format::Spinner`] renders as
+    /// [`Format::Plain`] since spinners can't draw to a buffer. Questions, progress and tasks are skipped since they
+    /// can't be replayed faithfully.
+    pub fn render(&self, format: Format, options: Options) -> String {
+        let buffer = SharedBuffer::default();
+        let mut fmt = new(format, options.with_custom_output_target(buffer.clone()));
+        let mut guards: Vec<Box<dyn IndentGuard>> = Vec::new();
+
+        for event in self.events() {
+            while guards.len() < event.depth.into() {
+                guards.push(fmt.indent());
+            }
+            guards.truncate(event.depth.into());
+
+            let fields = to_fields(&event.fields);
+            let metadata = to_fields(&event.metadata);
+
+            let message = RenderedValue {
+                text: &event.message,
+                value: &event.data,
+            };
+
+            if !event.only.is_empty() {
+                fmt.only(event.only.clone());
+            }
+            let target = with_owned_fields(fmt.as_mut(), &fields);
+            let target = with_owned_metadata(target, &metadata);
+
+            match event.kind {
+                EventKind::Print => target.print(&message),
+                EventKind::Println => target.println(&message),
+                EventKind::Error => target.error(&message),
+                EventKind::Success => target.success(&message),
+                EventKind::Warning => target.warning(&message),
+                EventKind::Debug => target.debug(&message),
+                EventKind::Trace => target.trace(&message),
+                EventKind::Spacer => target.spacer(),
+                EventKind::Table => {
+                    let headers: Vec<String> =
+                        serde_json::from_value(event.data["headers"].clone()).unwrap_or_default();
+                    let headers: Vec<&str> = headers.iter().map(String::as_str).collect();
+                    let rows: Vec<Vec<String>> =
+                        serde_json::from_value(event.data["rows"].clone()).unwrap_or_default();
+                    target.table(&headers, &rows);
+                }
+                EventKind::Question | EventKind::Progress | EventKind::Task => {
+                    // Clear out the filter and fields so they don't leak into the next event.
+                    target.with_fields(&[]).with_metadata(&[]).only(Vec::new());
+                }
+            }
+        }
+
+        drop(guards);
+        fmt.finish();
+        buffer.into_string()
+    }
+}
+
+struct Guard {
+    inner: Weak<Mutex<Inner>>,
+}
+
+impl IndentGuard for Guard {}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        if let Some(inner) = self.inner.upgrade() {
+            let mut inner = inner.lock().unwrap();
+            inner.depth = inner.depth.saturating_sub(1);
+        }
+    }
+}
+
+/// Records progress updates as [`EventKind::Progress`] events.
+struct CaptureProgress {
+    inner: Arc<Mutex<Inner>>,
+    state: Mutex<ProgressState>,
+}
+
+impl CaptureProgress {
+    fn record(&self) {
+        let state = self.state.lock().unwrap();
+        let data = json!({
+            "current": state.position,
+            "total": state.total,
+        });
+        self.inner
+            .lock()
+            .unwrap()
+            .record(EventKind::Progress, state.message.clone(), data);
+    }
+}
+
+impl ProgressHandle for CaptureProgress {
+    fn inc(&self, delta: u64) {
+        self.state.lock().unwrap().inc(delta);
+        self.record();
+    }
+
+    fn set_position(&self, position: u64) {
+        self.state.lock().unwrap().set_position(position);
+        self.record();
+    }
+
+    fn set_message(&self, msg: &dyn Displayable) {
+        self.state.lock().unwrap().message = msg.to_string();
+    }
+
+    fn finish(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.finished {
+            return;
+        }
+
+        state.finished = true;
+        state.position = state.total;
+        drop(state);
+
+        self.record();
+    }
+}
+
+/// Records task updates as [`EventKind::Task`] events.
+struct CaptureTask {
+    inner: Arc<Mutex<Inner>>,
+    name: String,
+    finished: Mutex<bool>,
+}
+
+impl CaptureTask {
+    fn record(&self, status: &str, msg: &dyn Displayable) {
+        let data = json!({
+            "task": self.name,
+            "status": status,
+        });
+        self.inner
+            .lock()
+            .unwrap()
+            .record(EventKind::Task, msg.to_string(), data);
+    }
+
+    /// Records the final status of the task, unless the task has already finished.
+    fn finish(&self, status: &str, msg: &dyn Displayable) {
+        let mut finished = self.finished.lock().unwrap();
+        if *finished {
+            return;
+        }
+
+        *finished = true;
+        self.record(status, msg);
+    }
+}
+
+impl TaskHandle for CaptureTask {
+    fn set_message(&self, msg: &dyn Displayable) {
+        if *self.finished.lock().unwrap() {
+            return;
+        }
+
+        self.record("running", msg);
+    }
+
+    fn success(&self, msg: &dyn Displayable) {
+        self.finish("success", msg);
+    }
+
+    fn error(&self, msg: &dyn Displayable) {
+        self.finish("error", msg);
+    }
+}
+
+/// Turns recorded fields back into [`Field`]s so they can be replayed.
+fn to_fields(recorded: &[(String, serde_json::Value)]) -> Vec<Field> {
+    recorded
+        .iter()
+        .map(|(key, value)| Field {
+            key: key.clone(),
+            text: match value {
+                serde_json::Value::String(text) => text.clone(),
+                other => other.to_string(),
+            },
+            value: value.clone(),
+        })
+        .collect()
+}
+
+impl Formatter for Capture {
+    fn print(&mut self, msg: &dyn Displayable) {
+        self.inner
+            .lock()
+            .unwrap()
+            .record_message(EventKind::Print, msg);
+    }
+
+    fn println(&mut self, msg: &dyn Displayable) {
+        self.inner
+            .lock()
+            .unwrap()
+            .record_message(EventKind::Println, msg);
+    }
+
+    fn error(&mut self, msg: &dyn Displayable) {
+        self.inner
+            .lock()
+            .unwrap()
+            .record_message(EventKind::Error, msg);
+    }
+
+    fn success(&mut self, msg: &dyn Displayable) {
+        self.inner
+            .lock()
+            .unwrap()
+            .record_message(EventKind::Success, msg);
+    }
+
+    fn warning(&mut self, msg: &dyn Displayable) {
+        self.inner
+            .lock()
+            .unwrap()
+            .record_message(EventKind::Warning, msg);
+    }
+
+    fn debug(&mut self, msg: &dyn Displayable) {
+        self.inner
+            .lock()
+            .unwrap()
+            .record_message(EventKind::Debug, msg);
+    }
+
+    fn trace(&mut self, msg: &dyn Displayable) {
+        self.inner
+            .lock()
+            .unwrap()
+            .record_message(EventKind::Trace, msg);
+    }
+
+    fn indent(&mut self) -> Box<dyn IndentGuard> {
+        self.inner.lock().unwrap().depth += 1;
+        Box::new(Guard {
+            inner: Arc::downgrade(&self.inner),
+        })
+    }
+
+    fn outdent(&mut self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.depth = inner.depth.saturating_sub(1);
+    }
+
+    fn spacer(&mut self) {
+        self.inner.lock().unwrap().record(
+            EventKind::Spacer,
+            String::new(),
+            serde_json::Value::Null,
+        );
+    }
+
+    fn table(&mut self, headers: &[&str], rows: &[Vec<String>]) {
+        let data = json!({
+            "headers": headers,
+            "rows": rows,
+        });
+        self.inner
+            .lock()
+            .unwrap()
+            .record(EventKind::Table, String::new(), data);
+    }
+
+    fn progress(&mut self, total: u64, msg: &dyn Displayable) -> Box<dyn ProgressHandle> {
+        let progress = CaptureProgress {
+            inner: Arc::clone(&self.inner),
+            state: Mutex::new(ProgressState::new(total, msg)),
+        };
+        progress.record();
+        Box::new(progress)
+    }
+
+    fn task(&mut self, name: &dyn Displayable) -> Box<dyn TaskHandle> {
+        let task = CaptureTask {
+            inner: Arc::clone(&self.inner),
+            name: name.to_string(),
+            finished: Mutex::new(false),
+        };
+        task.record("started", name);
+        Box::new(task)
+    }
+
+    fn pause(&mut self) {}
+
+    fn resume(&mut self) {}
+
+    fn question(&mut self, msg: &dyn Displayable) -> String {
+        let mut inner = self.inner.lock().unwrap();
+        inner.record_message(EventKind::Question, msg);
+        inner.answers.pop_front().unwrap_or_default()
+    }
+
+    fn only(&mut self, types: Vec<Format>) -> &mut dyn Formatter {
+        self.inner.lock().unwrap().only = types;
+        self
+    }
+
+    fn with_fields(&mut self, fields: &[(&str, &dyn Displayable)]) -> &mut dyn Formatter {
+        self.inner.lock().unwrap().fields = fields
+            .iter()
+            .map(|(key, value)| Field::new(key, *value))
+            .collect();
+        self
+    }
+
+    fn with_metadata(&mut self, fields: &[(&str, &dyn Displayable)]) -> &mut dyn Formatter {
+        self.inner.lock().unwrap().metadata = fields
+            .iter()
+            .map(|(key, value)| Field::new(key, *value))
+            .collect();
+        self
+    }
+
+    fn format(&self) -> Format {
+        self.inner.lock().unwrap().format.clone()
+    }
+
+    fn finish(&self) {}
+}
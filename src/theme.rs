@@ -0,0 +1,284 @@
+use crate::ansi;
+use colored::Color;
+use std::fmt::{self, Display};
+use unicode_width::UnicodeWidthStr;
+
+/// A piece of decoration drawn by the human readable formatters, like the `✓` in front of success messages or the
+/// `├─` connectors of the tree format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glyph {
+    pub text: String,
+    pub color: Option<Color>,
+    pub bold: bool,
+    pub dimmed: bool,
+}
+
+impl Glyph {
+    /// Creates an uncolored, unstyled glyph.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            color: None,
+            bold: false,
+            dimmed: false,
+        }
+    }
+
+    /// Sets the color of the glyph. Any [`Color`] works, including `Color::TrueColor` for brand colors.
+    pub fn color(self, color: Color) -> Self {
+        Self {
+            color: Some(color),
+            ..self
+        }
+    }
+
+    pub fn bold(self) -> Self {
+        Self { bold: true, ..self }
+    }
+
+    pub fn dimmed(self) -> Self {
+        Self {
+            dimmed: true,
+            ..self
+        }
+    }
+
+    /// Removes the color and any styling, leaving just the text.
+    pub fn plain(self) -> Self {
+        Self::new(self.text)
+    }
+
+    /// Number of columns the glyph takes up.
+    pub(crate) fn width(&self) -> u16 {
+        self.text.width().try_into().unwrap_or(u16::MAX)
+    }
+}
+
+/// Always includes the escape sequences for the color and style of the glyph. Formatters strip them again when
+/// colors are turned off.
+impl Display for Glyph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = ansi::paint(&self.text, self.color, self.bold, self.dimmed);
+        write!(f, "{text}")
+    }
+}
+
+/// Characters used to draw the borders of boxed tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Borders {
+    pub horizontal: char,
+    pub vertical: char,
+    pub top_left: char,
+    pub top_middle: char,
+    pub top_right: char,
+    pub middle_left: char,
+    pub middle: char,
+    pub middle_right: char,
+    pub bottom_left: char,
+    pub bottom_middle: char,
+    pub bottom_right: char,
+}
+
+impl Borders {
+    pub const UNICODE: Borders = Borders {
+        horizontal: '─',
+        vertical: '│',
+        top_left: '┌',
+        top_middle: '┬',
+        top_right: '┐',
+        middle_left: '├',
+        middle: '┼',
+        middle_right: '┤',
+        bottom_left: '└',
+        bottom_middle: '┴',
+        bottom_right: '┘',
+    };
+
+    pub const ASCII: Borders = Borders {
+        horizontal: '-',
+        vertical: '|',
+        top_left: '+',
+        top_middle: '+',
+        top_right: '+',
+        middle_left: '+',
+        middle: '+',
+        middle_right: '+',
+        bottom_left: '+',
+        bottom_middle: '+',
+        bottom_right: '+',
+    };
+}
+
+/// Describes the glyphs and colors used by the human readable formatters ([Plain](crate::Format::Plain),
+/// [Tree](crate::Format::Tree) and [Spinner](crate::Format::Spinner)).
+///
+/// Start from one of the built-in themes and override what you need:
+///
+/// ```
+/// use polyfmt::{Color, Glyph, Theme};
+///
+/// let brand = Theme {
+///     success: Glyph::new("✓").color(Color::TrueColor { r: 0, g: 200, b: 150 }),
+///     ..Theme::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    pub error: Glyph,
+    pub success: Glyph,
+    pub warning: Glyph,
+    pub question: Glyph,
+    pub debug: Glyph,
+    pub trace: Glyph,
+
+    /// Connector in front of the very first tree line.
+    pub tree_start: Glyph,
+
+    /// Connector in front of every other tree line.
+    pub tree_branch: Glyph,
+
+    /// Repeated after the connector once per indentation level.
+    pub tree_line: Glyph,
+
+    /// Drawn in front of continuation lines, including the trailing space.
+    pub tree_gutter: Glyph,
+
+    /// Drawn by [spacer](crate::Formatter::spacer).
+    pub tree_spacer: Glyph,
+
+    /// Animation frames of the spinner.
+    pub spinner_frames: Vec<String>,
+
+    /// Borders of the boxed tables drawn by the tree format.
+    pub table_borders: Borders,
+
+    /// Marks table cells which were truncated to fit the line length.
+    pub ellipsis: String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            error: Glyph::new("x").color(Color::Red),
+            success: Glyph::new("✓").color(Color::Green),
+            warning: Glyph::new("!!").color(Color::Yellow),
+            question: Glyph::new("?").color(Color::Magenta),
+            debug: Glyph::new("[debug]").dimmed(),
+            trace: Glyph::new("[trace]").dimmed(),
+            tree_start: Glyph::new("┌─").color(Color::Magenta),
+            tree_branch: Glyph::new("├─").color(Color::Magenta),
+            tree_line: Glyph::new("─").color(Color::Magenta),
+            tree_gutter: Glyph::new("│ ").color(Color::Magenta),
+            tree_spacer: Glyph::new("┊").color(Color::Magenta),
+            spinner_frames: ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"]
+                .map(String::from)
+                .to_vec(),
+            table_borders: Borders::UNICODE,
+            ellipsis: "…".to_string(),
+        }
+    }
+}
+
+impl Theme {
+    /// [`Theme::ascii`] when the terminal doesn't look like it can render Unicode, [`Theme::default`] otherwise.
+    ///
+    /// The guess is based on the locale (`LC_ALL`, `LC_CTYPE` and `LANG`) and `TERM`. On Windows, where the legacy
+    /// console usually has no locale set, Unicode is only assumed inside Windows Terminal or a terminal that sets
+    /// `TERM_PROGRAM`.
+    pub fn detect() -> Self {
+        if supports_unicode(cfg!(windows), |key| std::env::var(key).ok()) {
+            Self::default()
+        } else {
+            Self::ascii()
+        }
+    }
+
+    /// Only uses ASCII characters, for terminals and log viewers that can't render Unicode.
+    ///
+    /// The tree is drawn with `+-` and `|`, the spinner cycles through `-\|/` and truncated table cells end in `...`.
+    /// Since the tree is printed as it goes it never knows which line is the last one, so there is no closing `\-`
+    /// connector.
+    pub fn ascii() -> Self {
+        let default = Self::default();
+
+        Self {
+            error: Glyph::new("[x]").color(Color::Red),
+            success: Glyph::new("[ok]").color(Color::Green),
+            tree_start: Glyph::new("+-").color(Color::Magenta),
+            tree_branch: Glyph::new("+-").color(Color::Magenta),
+            tree_line: Glyph::new("-").color(Color::Magenta),
+            tree_gutter: Glyph::new("| ").color(Color::Magenta),
+            tree_spacer: Glyph::new(":").color(Color::Magenta),
+            spinner_frames: ["-", "\\", "|", "/"].map(String::from).to_vec(),
+            table_borders: Borders::ASCII,
+            ellipsis: "...".to_string(),
+            ..default
+        }
+    }
+
+    /// Bright, bold colors which stand out on both light and dark backgrounds.
+    pub fn high_contrast() -> Self {
+        let default = Self::default();
+
+        Self {
+            error: default.error.color(Color::BrightRed).bold(),
+            success: default.success.color(Color::BrightGreen).bold(),
+            warning: default.warning.color(Color::BrightYellow).bold(),
+            question: default.question.color(Color::BrightCyan).bold(),
+            debug: Glyph::new("[debug]").color(Color::BrightWhite),
+            trace: Glyph::new("[trace]").color(Color::BrightWhite),
+            tree_start: default.tree_start.color(Color::BrightMagenta),
+            tree_branch: default.tree_branch.color(Color::BrightMagenta),
+            tree_line: default.tree_line.color(Color::BrightMagenta),
+            tree_gutter: default.tree_gutter.color(Color::BrightMagenta),
+            tree_spacer: default.tree_spacer.color(Color::BrightMagenta),
+            ..default
+        }
+    }
+
+    /// The default glyphs without any colors or styling.
+    pub fn monochrome() -> Self {
+        Self::default().without_colors()
+    }
+
+    /// The same glyphs without any colors or styling.
+    pub fn without_colors(self) -> Self {
+        Self {
+            error: self.error.plain(),
+            success: self.success.plain(),
+            warning: self.warning.plain(),
+            question: self.question.plain(),
+            debug: self.debug.plain(),
+            trace: self.trace.plain(),
+            tree_start: self.tree_start.plain(),
+            tree_branch: self.tree_branch.plain(),
+            tree_line: self.tree_line.plain(),
+            tree_gutter: self.tree_gutter.plain(),
+            tree_spacer: self.tree_spacer.plain(),
+            ..self
+        }
+    }
+}
+
+/// Guesses whether the terminal can render Unicode. Takes the environment as a function so it can be tested.
+pub(crate) fn supports_unicode(is_windows: bool, env: impl Fn(&str) -> Option<String>) -> bool {
+    let env = |key: &str| env(key).filter(|value| !value.trim().is_empty());
+
+    if let Some(term) = env("TERM")
+        && (term == "dumb" || term == "linux")
+    {
+        return false;
+    }
+
+    // The first locale variable that is set wins, the same order the C library resolves them in.
+    if let Some(locale) = ["LC_ALL", "LC_CTYPE", "LANG"].into_iter().find_map(env) {
+        let locale = locale.to_ascii_lowercase();
+        return locale.contains("utf-8") || locale.contains("utf8");
+    }
+
+    if is_windows {
+        return env("WT_SESSION").is_some() || env("TERM_PROGRAM").is_some();
+    }
+
+    true
+}
@@ -0,0 +1,116 @@
+use crate::{Timestamps, ansi};
+use once_cell::sync::Lazy;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+
+/// When the first formatter was created, which is what elapsed times are measured from. Shared by every formatter
+/// so that a [`Tee`](crate::Tee) writing to several of them reports the same times.
+static START: Lazy<Instant> = Lazy::new(Instant::now);
+
+/// Reads the time for the timestamps printed in front of lines and added to Json records.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Clock {
+    mode: Timestamps,
+    start: Instant,
+}
+
+impl Clock {
+    pub(crate) fn new(mode: Timestamps) -> Self {
+        Self {
+            mode,
+            start: *START,
+        }
+    }
+
+    /// Time since the first formatter was created.
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// The current wall-clock time in RFC 3339 format, in UTC with millisecond precision.
+    pub(crate) fn now(&self) -> String {
+        humantime::format_rfc3339_millis(SystemTime::now()).to_string()
+    }
+
+    /// Renders the timestamp printed in front of a line, or nothing when timestamps are turned off.
+    pub(crate) fn prefix(&self) -> Option<String> {
+        match self.mode {
+            Timestamps::Off => None,
+            Timestamps::Rfc3339 => Some(self.now()),
+            Timestamps::Elapsed => Some(format_elapsed(self.elapsed())),
+        }
+    }
+
+    /// Number of columns taken up by the prefix, including the space separating it from the line.
+    pub(crate) fn width(&self) -> usize {
+        match self.mode {
+            Timestamps::Off => 0,
+            Timestamps::Rfc3339 => "2006-01-02T15:04:05.000Z ".len(),
+            Timestamps::Elapsed => format_elapsed(Duration::ZERO).len() + 1,
+        }
+    }
+
+    /// Prefixes a single line with the timestamp.
+    pub(crate) fn stamp(&self, line: &str) -> String {
+        match self.prefix() {
+            Some(prefix) => format!("{} {line}", ansi::dimmed(&prefix)),
+            None => line.to_string(),
+        }
+    }
+}
+
+/// Formats an elapsed time as seconds with millisecond precision, padded so that lines stay aligned for the first
+/// day of a run.
+pub(crate) fn format_elapsed(elapsed: Duration) -> String {
+    format!("{:>9.3}s", elapsed.as_secs_f64())
+}
+
+/// Writer which prefixes every line written to a target with a timestamp. The time is taken when the first byte of
+/// the line is written.
+pub(crate) struct Stamped {
+    inner: Arc<Mutex<dyn Write + Send>>,
+    clock: Clock,
+    at_line_start: bool,
+}
+
+impl Stamped {
+    /// Wraps the target, or returns it unchanged if timestamps are turned off.
+    pub fn wrap(inner: Arc<Mutex<dyn Write + Send>>, clock: Clock) -> Arc<Mutex<dyn Write + Send>> {
+        if clock.mode == Timestamps::Off {
+            return inner;
+        }
+
+        Arc::new(Mutex::new(Stamped {
+            inner,
+            clock,
+            at_line_start: true,
+        }))
+    }
+}
+
+impl Write for Stamped {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+
+        for line in buf.split_inclusive(|byte| *byte == b'\n') {
+            if self.at_line_start
+                && let Some(prefix) = self.clock.prefix()
+            {
+                write!(inner, "{} ", ansi::dimmed(&prefix))?;
+            }
+
+            inner.write_all(line)?;
+            self.at_line_start = line.ends_with(b"\n");
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .flush()
+    }
+}
@@ -0,0 +1,228 @@
+//! Bridge from [`tracing`](https://docs.rs/tracing) into polyfmt.
+//!
+//! [`PolyfmtLayer`] is a `tracing_subscriber` layer which writes events through a polyfmt [`Formatter`]. Entering a
+//! span increases the indentation of the formatter and exiting it drops the matching [`IndentGuard`], so nested spans
+//! show up as nested output (most visibly in the [`Tree`](crate::Format::Tree) format).
+//!
+//! Events map onto formatter calls by level:
+//!
+//! * `ERROR` → [`error`](Formatter::error)
+//! * `WARN` → [`warning`](Formatter::warning)
+//! * `INFO` → [`println`](Formatter::println)
+//! * `DEBUG` → [`debug`](Formatter::debug)
+//! * `TRACE` → [`trace`](Formatter::trace)
+//!
+//! Event fields are attached to the message as [fields](Formatter::with_fields). The fields of every enclosing span
+//! are attached as [metadata](Formatter::with_metadata), so they're included in [`Json`](crate::Format::Json)
+//! records but don't clutter human readable output.
+//!
+//! This module is only available with the `tracing` feature.
+
+use crate::{
+    Field, Formatter, IndentGuard, get_global_formatter, with_owned_fields, with_owned_metadata,
+};
+use ::tracing::field::{Field as TracingField, Visit};
+use ::tracing::span::{Attributes, Id, Record};
+use ::tracing::{Event, Level, Subscriber};
+use std::fmt::Debug;
+use std::sync::Mutex;
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// A `tracing_subscriber` layer which writes events through a polyfmt [`Formatter`].
+///
+/// # Example
+///
+/// ```
+/// use polyfmt::tracing::PolyfmtLayer;
+/// use tracing_subscriber::layer::SubscriberExt;
+///
+/// let subscriber = tracing_subscriber::registry().with(PolyfmtLayer::new());
+/// tracing::subscriber::with_default(subscriber, || {
+///     let _span = tracing::info_span!("deploy", region = "us").entered();
+///     tracing::info!(id = 42, "uploading artifacts");
+/// });
+/// ```
+#[derive(Default)]
+pub struct PolyfmtLayer {
+    /// Formatter to write through. When unset the global formatter is used.
+    formatter: Option<Mutex<Box<dyn Formatter>>>,
+}
+
+impl PolyfmtLayer {
+    /// Creates a layer which writes through the global formatter.
+    pub fn new() -> Self {
+        Self { formatter: None }
+    }
+
+    /// Creates a layer which writes through the given formatter instead of the global one.
+    pub fn with_formatter(formatter: Box<dyn Formatter>) -> Self {
+        Self {
+            formatter: Some(Mutex::new(formatter)),
+        }
+    }
+
+    fn with_fmt<R>(&self, f: impl FnOnce(&mut dyn Formatter) -> R) -> R {
+        match &self.formatter {
+            Some(formatter) => {
+                let mut fmt = formatter
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                f(fmt.as_mut())
+            }
+            None => {
+                let global_fmtter = get_global_formatter();
+                let mut fmt = global_fmtter
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                f(fmt.as_mut())
+            }
+        }
+    }
+}
+
+/// Fields recorded on a span, stored in the span's extensions.
+struct SpanFields(Vec<Field>);
+
+/// Indentation guards for every time a span has been entered but not yet exited.
+struct SpanGuards(Vec<Box<dyn IndentGuard>>);
+
+/// Collects tracing fields into polyfmt fields, pulling out the `message` field separately.
+#[derive(Default)]
+struct FieldVisitor {
+    message: Option<String>,
+    fields: Vec<Field>,
+}
+
+impl FieldVisitor {
+    fn push(&mut self, field: &TracingField, text: String, value: serde_json::Value) {
+        if field.name() == "message" {
+            self.message = Some(text);
+            return;
+        }
+
+        // Recording the same field twice (e.g. through `Span::record`) replaces the old value.
+        self.fields.retain(|existing| existing.key != field.name());
+        self.fields.push(Field {
+            key: field.name().to_string(),
+            text,
+            value,
+        });
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_i64(&mut self, field: &TracingField, value: i64) {
+        self.push(field, value.to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &TracingField, value: u64) {
+        self.push(field, value.to_string(), value.into());
+    }
+
+    fn record_f64(&mut self, field: &TracingField, value: f64) {
+        self.push(field, value.to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &TracingField, value: bool) {
+        self.push(field, value.to_string(), value.into());
+    }
+
+    fn record_str(&mut self, field: &TracingField, value: &str) {
+        self.push(field, value.to_string(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &TracingField, value: &dyn Debug) {
+        let text = format!("{value:?}");
+        self.push(field, text.clone(), text.into());
+    }
+}
+
+impl<S> Layer<S> for PolyfmtLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        let mut visitor = FieldVisitor::default();
+        attrs.record(&mut visitor);
+        span.extensions_mut().insert(SpanFields(visitor.fields));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        let mut extensions = span.extensions_mut();
+        let mut visitor = FieldVisitor {
+            message: None,
+            fields: extensions
+                .remove::<SpanFields>()
+                .map(|fields| fields.0)
+                .unwrap_or_default(),
+        };
+        values.record(&mut visitor);
+        extensions.insert(SpanFields(visitor.fields));
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        let guard = self.with_fmt(|fmt| fmt.indent());
+
+        let mut extensions = span.extensions_mut();
+        match extensions.get_mut::<SpanGuards>() {
+            Some(guards) => guards.0.push(guard),
+            None => extensions.insert(SpanGuards(vec![guard])),
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        let guard = span
+            .extensions_mut()
+            .get_mut::<SpanGuards>()
+            .and_then(|guards| guards.0.pop());
+        drop(guard);
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let message = visitor.message.take().unwrap_or_default();
+
+        self.with_fmt(|fmt| {
+            let mut metadata = Vec::new();
+
+            if let Some(scope) = ctx.event_scope(event) {
+                for span in scope.from_root() {
+                    if let Some(span_fields) = span.extensions().get::<SpanFields>() {
+                        metadata.retain(|field: &Field| {
+                            span_fields.0.iter().all(|other| other.key != field.key)
+                        });
+                        metadata.extend(span_fields.0.iter().cloned());
+                    }
+                }
+            }
+
+            let fmt = with_owned_fields(fmt, &visitor.fields);
+            let fmt = with_owned_metadata(fmt, &metadata);
+            match *event.metadata().level() {
+                Level::ERROR => fmt.error(&message),
+                Level::WARN => fmt.warning(&message),
+                Level::INFO => fmt.println(&message),
+                Level::DEBUG => fmt.debug(&message),
+                Level::TRACE => fmt.trace(&message),
+            }
+        });
+    }
+}